@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use rsay_tracing::{
+    BvhNode, Hittable, HittableList, Intersection, Lambertian, Material, Ray, Sphere, Vec3,
+};
+
+const SPHERES: usize = 500;
+const RAYS: usize = 1000;
+
+// Same spheres and rays on every run.
+fn setup() -> (Vec<Sphere>, Vec<Ray>) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let material: Arc<dyn Material> = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+
+    let mut point = |scale: f32| {
+        Vec3::new(
+            rng.gen_range(-scale..scale),
+            rng.gen_range(-scale..scale),
+            rng.gen_range(-scale..scale),
+        )
+    };
+    let spheres = (0..SPHERES)
+        .map(|_| Sphere {
+            center: point(20.),
+            radius: 0.5,
+            material: material.clone(),
+        })
+        .collect();
+    let rays = (0..RAYS)
+        .map(|_| Ray::new(Vec3::new(0., 0., 30.), point(1.) - Vec3::new(0., 0., 1.)))
+        .collect();
+    (spheres, rays)
+}
+
+fn boxed(spheres: &[Sphere]) -> Vec<Box<dyn Hittable>> {
+    spheres
+        .iter()
+        .map(|s| Box::new(s.clone()) as Box<dyn Hittable>)
+        .collect()
+}
+
+fn fire(world: &dyn Hittable, rays: &[Ray]) {
+    for ray in rays {
+        black_box(matches!(
+            world.hit(ray, 0.001, f32::INFINITY),
+            Intersection::Hit(_)
+        ));
+    }
+}
+
+fn bench_hit(c: &mut Criterion) {
+    let (spheres, rays) = setup();
+    let list = HittableList {
+        objects: boxed(&spheres),
+    };
+    let bvh = BvhNode::new(boxed(&spheres));
+
+    c.bench_function("hittable_list_hit", |b| b.iter(|| fire(&list, &rays)));
+    c.bench_function("bvh_hit", |b| b.iter(|| fire(&bvh, &rays)));
+}
+
+criterion_group!(benches, bench_hit);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> bool {
+        let (mut t_min, mut t_max) = (t_min, t_max);
+        for a in 0..3 {
+            let inv_d = 1. / ray.dir[a];
+            let mut t0 = (self.min[a] - ray.origin[a]) * inv_d;
+            let mut t1 = (self.max[a] - ray.origin[a]) * inv_d;
+            if inv_d < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max <= t_min {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Smallest box enclosing both a and b.
+    pub fn surrounding(a: Aabb, b: Aabb) -> Aabb {
+        Aabb {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(min: f32, max: f32) -> Aabb {
+        Aabb {
+            min: Vec3::new(min, min, min),
+            max: Vec3::new(max, max, max),
+        }
+    }
+
+    #[test]
+    fn ray_at_the_center_hits_and_away_misses() {
+        let b = cube(1., 2.);
+        let origin = Vec3::new(0., 0., 0.);
+        let center = Vec3::new(1.5, 1.5, 1.5);
+        assert!(b.hit(&Ray::new(origin, center), 0.001, f32::INFINITY));
+        assert!(!b.hit(&Ray::new(origin, -center), 0.001, f32::INFINITY));
+    }
+
+    #[test]
+    fn surrounding_contains_both_boxes() {
+        let (a, b) = (cube(-3., -2.), cube(1., 4.));
+        let s = Aabb::surrounding(a, b);
+        for c in &[a, b] {
+            assert_eq!(s.min.min(c.min), s.min);
+            assert_eq!(s.max.max(c.max), s.max);
+        }
+        assert_eq!(s.min, Vec3::new(-3., -3., -3.));
+        assert_eq!(s.max, Vec3::new(4., 4., 4.));
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::background::Background;
+use crate::bvh::bvh_or_list;
+use crate::camera::Camera;
+use crate::hittable::HittableList;
+use crate::output::{write_ppm, ToneMap};
+use crate::render::{render_with_seed, RenderConfig, RenderError, Sampling, Shading, Termination};
+use crate::vec3::Normalization;
+
+// Settings shared by every frame; the camera and world come per frame from
+// the scene closure.
+#[derive(Debug, Clone)]
+pub struct AnimationConfig {
+    pub image_width: i32,
+    pub image_height: i32,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub background: Background,
+    pub gamma: f32,
+    // Frame i renders with a seed derived from this and i.
+    pub seed: u64,
+    // Directory that receives frame_0000.ppm, frame_0001.ppm, ...
+    pub output_dir: String,
+    pub quiet: bool,
+}
+
+#[derive(Debug)]
+pub enum AnimationError {
+    Render(RenderError),
+    Io(io::Error),
+}
+
+impl fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnimationError::Render(e) => write!(f, "{}", e),
+            AnimationError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AnimationError {}
+
+impl From<RenderError> for AnimationError {
+    fn from(e: RenderError) -> AnimationError {
+        AnimationError::Render(e)
+    }
+}
+
+impl From<io::Error> for AnimationError {
+    fn from(e: io::Error) -> AnimationError {
+        AnimationError::Io(e)
+    }
+}
+
+// Renders frames evenly spaced over t in [0, 1], building each frame's
+// scene with scene_fn(t), and returns the paths of the written frames.
+pub fn render_animation(
+    frames: u32,
+    scene_fn: impl Fn(f32) -> (Camera, HittableList),
+    config: &AnimationConfig,
+) -> Result<Vec<String>, AnimationError> {
+    let mut paths = Vec::new();
+    for frame in 0..frames {
+        let t = match frames > 1 {
+            true => frame as f32 / (frames - 1) as f32,
+            false => 0.,
+        };
+        let (camera, world) = scene_fn(t);
+        let world = bvh_or_list(world);
+
+        let seed = config.seed ^ (frame as u64).wrapping_mul(0xD1B5_4A32_D192_ED03);
+        let image = render_with_seed(
+            seed,
+            RenderConfig {
+                image_width: config.image_width,
+                image_height: config.image_height,
+                samples_per_pixel: config.samples_per_pixel,
+                max_depth: config.max_depth,
+                camera: &camera,
+                world: world.as_ref(),
+                lights: None,
+                background: config.background.clone(),
+                sampling: Sampling::Random,
+                shading: Shading::Materials,
+                termination: Termination::FixedDepth,
+                min_bounces: 0,
+                firefly_clamp: None,
+                normalization: Normalization::Exact,
+                threads: None,
+                quiet: config.quiet,
+            },
+        )?;
+
+        let path = Path::new(&config.output_dir)
+            .join(format!("frame_{:04}.ppm", frame))
+            .to_string_lossy()
+            .into_owned();
+        write_ppm(
+            &path,
+            &image,
+            config.image_width,
+            config.image_height,
+            config.samples_per_pixel,
+            config.gamma,
+            ToneMap::Clamp,
+        )?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::builtin_scene;
+
+    #[test]
+    fn three_frames_write_three_padded_files() {
+        let dir = std::env::temp_dir().join(format!("rsay-tracing-{}-frames", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = AnimationConfig {
+            image_width: 4,
+            image_height: 3,
+            samples_per_pixel: 1,
+            max_depth: 4,
+            background: Background::sky(),
+            gamma: 2.,
+            seed: 85,
+            output_dir: dir.to_string_lossy().into_owned(),
+            quiet: true,
+        };
+        let paths = render_animation(3, |_| builtin_scene(4. / 3.), &config);
+
+        let mut names: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths.unwrap().len(), 3);
+        assert_eq!(
+            names,
+            vec!["frame_0000.ppm", "frame_0001.ppm", "frame_0002.ppm"]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+#[derive(Debug, Clone)]
+pub struct AxisAlignedBox {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub material: Arc<dyn Material>,
+}
+
+impl Hittable for AxisAlignedBox {
+    // Slab method: intersect the per-axis t intervals.
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let (mut t_enter, mut enter_axis) = (f32::NEG_INFINITY, 0);
+        let (mut t_exit, mut exit_axis) = (f32::INFINITY, 0);
+        for a in 0..3 {
+            let inv = 1. / ray.dir[a];
+            let mut t0 = (self.min[a] - ray.origin[a]) * inv;
+            let mut t1 = (self.max[a] - ray.origin[a]) * inv;
+            if inv < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            if t0 > t_enter {
+                t_enter = t0;
+                enter_axis = a;
+            }
+            if t1 < t_exit {
+                t_exit = t1;
+                exit_axis = a;
+            }
+        }
+
+        if t_enter > t_exit {
+            return Intersection::Missed;
+        }
+
+        // Prefer the entry face; fall back to the exit face when the ray starts inside.
+        let (t, axis, sign) = match (t_min..=t_max).contains(&t_enter) {
+            true => (t_enter, enter_axis, -1.),
+            false if (t_min..=t_max).contains(&t_exit) => (t_exit, exit_axis, 1.),
+            false => return Intersection::Missed,
+        };
+
+        let mut outward_normal = Vec3::new(0., 0., 0.);
+        outward_normal[axis] = sign * ray.dir[axis].signum();
+
+        Intersection::Hit(Hit::facing(
+            t,
+            ray.at(t),
+            ray,
+            outward_normal,
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb {
+            min: self.min,
+            max: self.max,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn unit_box() -> AxisAlignedBox {
+        AxisAlignedBox {
+            min: Vec3::new(-1., -1., -1.),
+            max: Vec3::new(1., 1., 1.),
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    #[test]
+    fn ray_enters_through_the_plus_x_face() {
+        let ray = Ray::new(Vec3::new(5., 0.2, -0.3), Vec3::new(-1., 0., 0.));
+        match unit_box().hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                assert_eq!(h.t, 4.);
+                assert_eq!(h.normal, Vec3::new(1., 0., 0.));
+                assert!(h.front);
+            }
+            Intersection::Missed => panic!("ray toward the +x face missed"),
+        }
+    }
+
+    #[test]
+    fn ray_passing_alongside_misses() {
+        let ray = Ray::new(Vec3::new(5., 1.5, 0.), Vec3::new(-1., 0., 0.));
+        assert!(matches!(
+            unit_box().hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+}
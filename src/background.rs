@@ -0,0 +1,115 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::ray::Ray;
+use crate::texture::{nearest_texel, EMPTY_IMAGE_COLOR};
+use crate::vec3::{clip, Vec3};
+
+#[derive(Debug, Clone)]
+pub enum Background {
+    SolidColor(Vec3),
+    // Vertical lerp on the ray direction's y component.
+    Gradient { top: Vec3, bottom: Vec3 },
+    // Image-based lighting from an equirectangular map.
+    Environment(Arc<EnvironmentMap>),
+}
+
+// Colors of the default sky.
+pub(crate) const SKY_TOP: Vec3 = Vec3 {
+    x: 0.5,
+    y: 0.7,
+    z: 1.0,
+};
+const SKY_BOTTOM: Vec3 = Vec3 {
+    x: 1.0,
+    y: 1.0,
+    z: 1.0,
+};
+
+// The default sky gradient seen along dir: white below, blue overhead.
+pub fn sky_color(dir: Vec3) -> Vec3 {
+    gradient(dir, SKY_TOP, SKY_BOTTOM)
+}
+
+// Vertical lerp on the unit direction's y component.
+fn gradient(dir: Vec3, top: Vec3, bottom: Vec3) -> Vec3 {
+    let t = 0.5 * (dir.unit().y + 1.0);
+    bottom.lerp(top, t)
+}
+
+impl Background {
+    // Gradient with the default sky colors.
+    pub fn sky() -> Background {
+        Background::Gradient {
+            top: SKY_TOP,
+            bottom: SKY_BOTTOM,
+        }
+    }
+
+    pub fn color(&self, ray: &Ray) -> Vec3 {
+        match self {
+            Background::SolidColor(c) => *c,
+            Background::Gradient { top, bottom } => gradient(ray.dir, *top, *bottom),
+            Background::Environment(map) => map.sample(ray.dir),
+        }
+    }
+}
+
+// Equirectangular (latitude/longitude) image, typically HDR, with +y at the
+// top row.
+#[derive(Debug)]
+pub struct EnvironmentMap {
+    image: image::Rgb32FImage,
+}
+
+impl EnvironmentMap {
+    pub fn new(path: &str) -> image::ImageResult<EnvironmentMap> {
+        Ok(EnvironmentMap {
+            image: image::open(path)?.to_rgb32f(),
+        })
+    }
+
+    // Radiance seen along dir, from the nearest texel.
+    pub fn sample(&self, dir: Vec3) -> Vec3 {
+        let d = dir.unit();
+        let u = d.z.atan2(d.x) / (2. * PI) + 0.5;
+        let v = clip(d.y, -1., 1.).acos() / PI;
+        match nearest_texel(&self.image, u, v) {
+            Some(texel) => {
+                let [r, g, b] = texel.0;
+                Vec3::new(r, g, b)
+            }
+            None => EMPTY_IMAGE_COLOR,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn straight_up_samples_the_top_row() {
+        // Red top row over a blue bottom row.
+        let image = image::Rgb32FImage::from_fn(4, 2, |_, j| match j {
+            0 => image::Rgb([1., 0., 0.]),
+            _ => image::Rgb([0., 0., 1.]),
+        });
+        let map = EnvironmentMap { image };
+        assert_eq!(map.sample(Vec3::new(0., 1., 0.)), Vec3::new(1., 0., 0.));
+        assert_eq!(map.sample(Vec3::new(0., -1., 0.)), Vec3::new(0., 0., 1.));
+    }
+
+    #[test]
+    fn black_background_is_exactly_black_on_a_miss() {
+        use crate::hittable::HittableList;
+        use crate::render::ray_color;
+
+        let black = Background::SolidColor(Vec3::new(0., 0., 0.));
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0.3, 0.9, -1.));
+        assert_eq!(
+            ray_color(&ray, &HittableList::default(), &black, 8),
+            Vec3::new(0., 0., 0.)
+        );
+    }
+}
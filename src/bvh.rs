@@ -0,0 +1,152 @@
+use crate::aabb::Aabb;
+use crate::hittable::{Hittable, HittableList, Intersection};
+use crate::ray::Ray;
+
+// Bounding volume hierarchy over primitives that all have bounding boxes.
+// BvhNode::new panics on an empty list or an unbounded primitive; use
+// bvh_or_list for arbitrary scenes.
+pub struct BvhNode {
+    left: Box<dyn Hittable>,
+    right: Box<dyn Hittable>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(mut objects: Vec<Box<dyn Hittable>>) -> BvhNode {
+        let bounds = |obj: &dyn Hittable| {
+            obj.bounding_box()
+                .expect("BvhNode requires primitives with a bounding box")
+        };
+        let centroid = |obj: &dyn Hittable| {
+            let b = bounds(obj);
+            (b.min + b.max) * 0.5
+        };
+
+        // Split along the axis where the centroids are most spread out.
+        let spread = objects
+            .iter()
+            .map(|obj| {
+                let c = centroid(obj.as_ref());
+                Aabb { min: c, max: c }
+            })
+            .reduce(Aabb::surrounding)
+            .map(|b| b.max - b.min)
+            .expect("BvhNode requires at least one primitive");
+        let axis = match spread {
+            d if d.x >= d.y && d.x >= d.z => 0,
+            d if d.y >= d.z => 1,
+            _ => 2,
+        };
+        objects.sort_by(|a, b| centroid(a.as_ref())[axis].total_cmp(&centroid(b.as_ref())[axis]));
+
+        let (left, right): (Box<dyn Hittable>, Box<dyn Hittable>) = match objects.len() {
+            1 => (objects.pop().unwrap(), Box::new(HittableList::default())),
+            2 => {
+                let right = objects.pop().unwrap();
+                (objects.pop().unwrap(), right)
+            }
+            n => {
+                let rest = objects.split_off(n / 2);
+                (
+                    Box::new(BvhNode::new(objects)),
+                    Box::new(BvhNode::new(rest)),
+                )
+            }
+        };
+
+        let bbox = match right.bounding_box() {
+            Some(b) => Aabb::surrounding(bounds(left.as_ref()), b),
+            None => bounds(left.as_ref()),
+        };
+
+        BvhNode { left, right, bbox }
+    }
+}
+
+// The list's objects in a BVH, or the list itself when it's empty or holds
+// primitives without a bounding box (e.g. planes).
+pub fn bvh_or_list(list: HittableList) -> Box<dyn Hittable> {
+    match list.bounding_box() {
+        Some(_) => Box::new(BvhNode::new(list.objects)),
+        None => Box::new(list),
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return Intersection::Missed;
+        }
+
+        let left = self.left.hit(ray, t_min, t_max);
+        let t_max = match &left {
+            Intersection::Hit(h) => h.t,
+            Intersection::Missed => t_max,
+        };
+        match self.right.hit(ray, t_min, t_max) {
+            Intersection::Missed => left,
+            right => right,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::sphere::Sphere;
+    use crate::vec3::Vec3;
+
+    fn t_of(hit: Intersection) -> Option<f32> {
+        match hit {
+            Intersection::Hit(h) => Some(h.t),
+            Intersection::Missed => None,
+        }
+    }
+
+    #[test]
+    fn bvh_matches_linear_list() {
+        let material = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+        let mut list = HittableList::default();
+        for _ in 0..50 {
+            list.add(Box::new(Sphere {
+                center: Vec3::random(-10., 10.),
+                radius: Vec3::random(0.2, 1.5).x,
+                material: material.clone(),
+            }));
+        }
+        let rays: Vec<Ray> = (0..200)
+            .map(|_| {
+                Ray::new(
+                    Vec3::new(0., 0., 30.),
+                    Vec3::random(-1., 1.) - Vec3::new(0., 0., 1.),
+                )
+            })
+            .collect();
+        let linear: Vec<_> = rays
+            .iter()
+            .map(|r| t_of(list.hit(r, 0.001, f32::INFINITY)))
+            .collect();
+
+        let bvh = BvhNode::new(list.objects);
+        for (ray, expected) in rays.iter().zip(linear) {
+            assert_eq!(t_of(bvh.hit(ray, 0.001, f32::INFINITY)), expected);
+        }
+    }
+
+    #[test]
+    fn empty_list_falls_back_to_the_list() {
+        let world = bvh_or_list(HittableList::default());
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        assert!(matches!(
+            world.hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+}
@@ -0,0 +1,335 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::aabb::Aabb;
+use crate::ray::Ray;
+use crate::rng::random_f32;
+use crate::vec3::{random_in_unit_disk, Vec3};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Projection {
+    Perspective,
+    // Parallel rays along the view direction.
+    Orthographic,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Camera {
+    lower_left_corner: Vec3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    origin: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    lens_radius: f32,
+    projection: Projection,
+    // Rotation about the view axis applied on top of the vup basis.
+    roll_deg: f32,
+    // Shutter open/close times.
+    time0: f32,
+    time1: f32,
+}
+
+impl Camera {
+    pub fn new(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        vfov_deg: f32,
+        aspect_ratio: f32,
+        aperture: f32,
+        focus_dist: f32,
+    ) -> Camera {
+        let h = (vfov_deg.to_radians() / 2.).tan();
+        let viewport_height = 2.0 * h;
+        let viewport_width = aspect_ratio * viewport_height;
+
+        // Orthonormal camera basis.
+        let w = (lookfrom - lookat).unit();
+        let u = vup.cross(w).unit();
+        let v = w.cross(u);
+
+        // The viewport sits on the focal plane.
+        let origin = lookfrom;
+        let horizontal = u * viewport_width * focus_dist;
+        let vertical = v * viewport_height * focus_dist;
+
+        Camera {
+            origin,
+            horizontal,
+            vertical,
+            lower_left_corner: origin - horizontal / 2. - vertical / 2. - w * focus_dist,
+            u,
+            v,
+            w,
+            lens_radius: aperture / 2.,
+            projection: Projection::Perspective,
+            roll_deg: 0.,
+            time0: 0.,
+            time1: 0.,
+        }
+    }
+
+    // Perspective camera looking at bbox from the front and slightly above,
+    // far enough back that the box's bounding sphere fits the view.
+    pub fn frame_scene(bbox: Aabb, aspect_ratio: f32) -> Camera {
+        let vfov_deg: f32 = 40.;
+        let center = (bbox.min + bbox.max) / 2.;
+        // Kept positive so a point-sized box doesn't put the camera inside it.
+        let radius = (bbox.min.distance(bbox.max) / 2.).max(1e-3);
+
+        // The narrower of the two fields of view limits the fit.
+        let half_v = vfov_deg.to_radians() / 2.;
+        let half_h = (aspect_ratio * half_v.tan()).atan();
+        let distance = radius / half_v.min(half_h).sin();
+
+        let lookfrom = center + Vec3::new(0., 0.3, 1.).unit() * distance;
+        Camera::new(
+            lookfrom,
+            center,
+            Vec3::new(0., 1., 0.),
+            vfov_deg,
+            aspect_ratio,
+            0.,
+            distance,
+        )
+    }
+
+    // Parallel projection with the viewport size given in world units.
+    pub fn orthographic(
+        lookfrom: Vec3,
+        lookat: Vec3,
+        vup: Vec3,
+        viewport_width: f32,
+        viewport_height: f32,
+    ) -> Camera {
+        let w = (lookfrom - lookat).unit();
+        let u = vup.cross(w).unit();
+        let v = w.cross(u);
+
+        let origin = lookfrom;
+        let horizontal = u * viewport_width;
+        let vertical = v * viewport_height;
+
+        Camera {
+            origin,
+            horizontal,
+            vertical,
+            lower_left_corner: origin - horizontal / 2. - vertical / 2.,
+            u,
+            v,
+            w,
+            lens_radius: 0.,
+            projection: Projection::Orthographic,
+            roll_deg: 0.,
+            time0: 0.,
+            time1: 0.,
+        }
+    }
+
+    pub fn with_shutter(self, time0: f32, time1: f32) -> Camera {
+        Camera {
+            time0,
+            time1,
+            ..self
+        }
+    }
+
+    // Changes the vertical field of view in place, keeping the position,
+    // orientation and focus distance. Orthographic cameras have no field of
+    // view and are left unchanged.
+    pub fn set_vfov(&mut self, vfov_deg: f32) {
+        if self.projection == Projection::Orthographic {
+            return;
+        }
+        let focus_dist = self.origin.distance(self.viewport_center());
+        let viewport_height = 2. * (vfov_deg.to_radians() / 2.).tan() * focus_dist;
+        self.scale_viewport(viewport_height / self.vertical.len());
+    }
+
+    // Rolls the camera about its view axis (dutch angle). The angle is
+    // absolute: 0 restores the orientation given by vup.
+    pub fn set_roll(&mut self, roll_deg: f32) {
+        let (sin, cos) = (roll_deg - self.roll_deg).to_radians().sin_cos();
+        let center = self.viewport_center();
+        let (width, height) = (self.horizontal.len(), self.vertical.len());
+
+        let u = self.u * cos + self.v * sin;
+        let v = self.v * cos - self.u * sin;
+        self.u = u;
+        self.v = v;
+        self.horizontal = u * width;
+        self.vertical = v * height;
+        self.lower_left_corner = center - self.horizontal / 2. - self.vertical / 2.;
+        self.roll_deg = roll_deg;
+    }
+
+    // Magnifies the image by factor (> 1 zooms in) without moving the camera.
+    pub fn zoom(&mut self, factor: f32) {
+        self.scale_viewport(1. / factor);
+    }
+
+    fn viewport_center(&self) -> Vec3 {
+        self.lower_left_corner + self.horizontal / 2. + self.vertical / 2.
+    }
+
+    // Resizes the viewport about its center, so the center ray is unchanged.
+    fn scale_viewport(&mut self, k: f32) {
+        let center = self.viewport_center();
+        self.horizontal *= k;
+        self.vertical *= k;
+        self.lower_left_corner = center - self.horizontal / 2. - self.vertical / 2.;
+    }
+
+    pub fn get_ray(&self, s: f32, t: f32) -> Ray {
+        let time = self.time0 + (self.time1 - self.time0) * random_f32();
+
+        if self.projection == Projection::Orthographic {
+            return Ray::new_at_time(
+                self.lower_left_corner + self.horizontal * s + self.vertical * t,
+                -self.w,
+                time,
+            );
+        }
+
+        let rd = random_in_unit_disk() * self.lens_radius;
+        let offset = self.u * rd.x + self.v * rd.y;
+
+        Ray::new_at_time(
+            self.origin + offset,
+            self.lower_left_corner + self.horizontal * s + self.vertical * t - self.origin - offset,
+            time,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn center_ray_points_at_lookat() {
+        let lookfrom = Vec3::new(1., 2., 3.);
+        let lookat = Vec3::new(-2., 0.5, -4.);
+        let camera = Camera::new(lookfrom, lookat, Vec3::new(0., 1., 0.), 40., 1.5, 0., 1.);
+        let ray = camera.get_ray(0.5, 0.5);
+        assert_eq!(ray.origin, lookfrom);
+        let expected = (lookat - lookfrom).unit();
+        assert!(ray.dir.unit().approx_eq(expected, 1e-6), "{:?}", ray.dir);
+    }
+
+    #[test]
+    fn zero_aperture_is_a_pinhole() {
+        let lookfrom = Vec3::new(0., 1., 2.);
+        let camera = Camera::new(
+            lookfrom,
+            Vec3::new(0., 0., -1.),
+            Vec3::new(0., 1., 0.),
+            60.,
+            2.,
+            0.,
+            3.,
+        );
+        for &(s, t) in &[(0., 0.), (0.25, 0.8), (1., 1.)] {
+            let first = camera.get_ray(s, t);
+            assert_eq!(first.origin, lookfrom);
+            for _ in 0..10 {
+                let again = camera.get_ray(s, t);
+                assert_eq!((again.origin, again.dir), (first.origin, first.dir));
+            }
+        }
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let (lookfrom, lookat, vup) = (
+            Vec3::new(0., 0., 2.),
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+        );
+        let ortho = Camera::orthographic(lookfrom, lookat, vup, 4., 3.);
+        let perspective = Camera::new(lookfrom, lookat, vup, 60., 4. / 3., 0., 1.);
+        let corner_rays = |camera: &Camera| {
+            let (a, b) = (camera.get_ray(0., 0.), camera.get_ray(1., 1.));
+            (a.dir.unit(), b.dir.unit(), a.origin, b.origin)
+        };
+
+        let (a, b, a_origin, b_origin) = corner_rays(&ortho);
+        assert_eq!(a, b);
+        assert_eq!(a, Vec3::new(0., 0., -1.));
+        assert_ne!(a_origin, b_origin);
+
+        let (a, b, a_origin, b_origin) = corner_rays(&perspective);
+        assert!(!a.approx_eq(b, 0.1));
+        assert_eq!(a_origin, b_origin);
+    }
+
+    #[test]
+    fn framed_scene_has_every_corner_in_view() {
+        let bbox = Aabb {
+            min: Vec3::new(-3., -1., -2.),
+            max: Vec3::new(5., 2., 1.),
+        };
+        let camera = Camera::frame_scene(bbox, 16. / 9.);
+
+        // Where the ray from the camera through p crosses the viewport, in
+        // the same (s, t) coordinates get_ray takes.
+        let project = |p: Vec3| {
+            let forward = -camera.w;
+            let focus = (camera.viewport_center() - camera.origin).dot(forward);
+            let d = p - camera.origin;
+            let q = camera.origin + d * (focus / d.dot(forward)) - camera.lower_left_corner;
+            (
+                q.dot(camera.horizontal) / camera.horizontal.len2(),
+                q.dot(camera.vertical) / camera.vertical.len2(),
+            )
+        };
+        let (s, t) = project(camera.origin + camera.get_ray(0.2, 0.7).dir * 3.);
+        assert!(
+            (s - 0.2).abs() < 1e-4 && (t - 0.7).abs() < 1e-4,
+            "{} {}",
+            s,
+            t
+        );
+
+        for corner in 0..8 {
+            let pick = |bit: usize, axis: usize| match corner & bit {
+                0 => bbox.min[axis],
+                _ => bbox.max[axis],
+            };
+            let p = Vec3::new(pick(1, 0), pick(2, 1), pick(4, 2));
+            let (s, t) = project(p);
+            assert!((0. ..=1.).contains(&s), "{:?} at s = {}", p, s);
+            assert!((0. ..=1.).contains(&t), "{:?} at t = {}", p, t);
+        }
+    }
+
+    #[test]
+    fn quarter_roll_swaps_extents_and_keeps_the_center() {
+        let mut camera = Camera::new(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 0., -1.),
+            Vec3::new(0., 1., 0.),
+            90.,
+            2.,
+            0.,
+            1.,
+        );
+        let center = camera.get_ray(0.5, 0.5).dir;
+        let (h, v) = (camera.horizontal, camera.vertical);
+        camera.set_roll(90.);
+
+        // The image's horizontal now runs up the old vertical, and its
+        // vertical along the old horizontal: world width and height swap.
+        assert!(camera.horizontal.approx_eq(v.unit() * h.len(), 1e-5));
+        assert!(camera.vertical.approx_eq(-h.unit() * v.len(), 1e-5));
+        let extent =
+            |axis: Vec3| camera.horizontal.dot(axis).abs() + camera.vertical.dot(axis).abs();
+        assert!((extent(Vec3::new(1., 0., 0.)) - v.len()).abs() < 1e-5);
+        assert!((extent(Vec3::new(0., 1., 0.)) - h.len()).abs() < 1e-5);
+        assert!(camera.get_ray(0.5, 0.5).dir.approx_eq(center, 1e-6));
+    }
+}
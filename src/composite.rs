@@ -0,0 +1,99 @@
+use std::fmt;
+
+use crate::vec3::Vec3;
+
+#[derive(Debug)]
+pub enum CompositeError {
+    // Buffers must cover the same pixels.
+    Length { expected: usize, found: usize },
+    // Averaging needs at least one buffer.
+    Empty,
+}
+
+impl fmt::Display for CompositeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompositeError::Length { expected, found } => write!(
+                f,
+                "image buffers differ in length: expected {} pixels, got {}",
+                expected, found
+            ),
+            CompositeError::Empty => write!(f, "no image buffers to average"),
+        }
+    }
+}
+
+impl std::error::Error for CompositeError {}
+
+// Per-pixel a * weight_a + b * (1 - weight_a).
+pub fn blend_images(a: &[Vec3], b: &[Vec3], weight_a: f32) -> Result<Vec<Vec3>, CompositeError> {
+    if a.len() != b.len() {
+        return Err(CompositeError::Length {
+            expected: a.len(),
+            found: b.len(),
+        });
+    }
+    Ok(a.iter()
+        .zip(b)
+        .map(|(&pa, &pb)| pb.lerp(pa, weight_a))
+        .collect())
+}
+
+// Per-pixel mean, e.g. of full frames rendered with different seeds.
+pub fn average_images(bufs: &[Vec<Vec3>]) -> Result<Vec<Vec3>, CompositeError> {
+    let first = bufs.first().ok_or(CompositeError::Empty)?;
+    let mut sum = vec![Vec3::new(0., 0., 0.); first.len()];
+    for buf in bufs {
+        if buf.len() != first.len() {
+            return Err(CompositeError::Length {
+                expected: first.len(),
+                found: buf.len(),
+            });
+        }
+        for (s, &p) in sum.iter_mut().zip(buf) {
+            *s += p;
+        }
+    }
+    let scale = 1. / bufs.len() as f32;
+    Ok(sum.into_iter().map(|s| s * scale).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> Vec<Vec3> {
+        vec![
+            Vec3::new(0.1, 0.2, 0.3),
+            Vec3::new(1., 0.5, 0.),
+            Vec3::new(4., 4., 4.),
+        ]
+    }
+
+    #[test]
+    fn averaging_or_blending_an_image_with_itself_is_the_identity() {
+        let a = image();
+        assert_eq!(average_images(&[a.clone(), a.clone()]).unwrap(), a);
+        assert_eq!(blend_images(&a, &a, 0.3).unwrap(), a);
+    }
+
+    #[test]
+    fn mismatched_lengths_are_an_error() {
+        let (a, b) = (image(), vec![Vec3::new(0., 0., 0.)]);
+        assert!(matches!(
+            average_images(&[a.clone(), b.clone()]),
+            Err(CompositeError::Length {
+                expected: 3,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            blend_images(&a, &b, 0.5),
+            Err(CompositeError::Length {
+                expected: 3,
+                found: 1
+            })
+        ));
+        assert!(matches!(average_images(&[]), Err(CompositeError::Empty)));
+    }
+}
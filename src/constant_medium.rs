@@ -0,0 +1,101 @@
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::material::{Isotropic, Material};
+use crate::ray::Ray;
+use crate::rng::random_f32;
+use crate::vec3::Vec3;
+
+// Participating medium (fog, smoke) filling a boundary shape with constant density.
+pub struct ConstantMedium {
+    pub boundary: Box<dyn Hittable>,
+    pub density: f32,
+    pub phase_function: Box<dyn Material>,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Box<dyn Hittable>, density: f32, albedo: Vec3) -> ConstantMedium {
+        ConstantMedium {
+            boundary,
+            density,
+            phase_function: Box::new(Isotropic::new(albedo)),
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        // Where the ray enters and leaves the boundary, ignoring the interval.
+        let t_enter = match self.boundary.hit(ray, f32::NEG_INFINITY, f32::INFINITY) {
+            Intersection::Hit(h) => h.t,
+            Intersection::Missed => return Intersection::Missed,
+        };
+        let t_exit = match self.boundary.hit(ray, t_enter + 0.0001, f32::INFINITY) {
+            Intersection::Hit(h) => h.t,
+            Intersection::Missed => return Intersection::Missed,
+        };
+
+        let t_enter = t_enter.max(t_min).max(0.);
+        let t_exit = t_exit.min(t_max);
+        if t_enter >= t_exit {
+            return Intersection::Missed;
+        }
+
+        let ray_length = ray.dir.len();
+        let distance_inside = (t_exit - t_enter) * ray_length;
+        let hit_distance = -(1. / self.density) * random_f32().ln();
+        if hit_distance > distance_inside {
+            return Intersection::Missed;
+        }
+
+        // Normal and facing are arbitrary inside a volume.
+        let t = t_enter + hit_distance / ray_length;
+        Intersection::Hit(Hit::new(
+            t,
+            ray.at(t),
+            Vec3::new(1., 0., 0.),
+            true,
+            self.phase_function.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::reseed;
+    use crate::sphere::Sphere;
+    use std::sync::Arc;
+
+    // Fraction of 1000 rays through the middle of a unit fog ball that scatter.
+    fn scattered_fraction(density: f32) -> f32 {
+        reseed(46);
+        let fog = ConstantMedium::new(
+            Box::new(Sphere {
+                center: Vec3::new(0., 0., -3.),
+                radius: 1.,
+                material: Arc::new(Isotropic::new(Vec3::new(1., 1., 1.))),
+            }),
+            density,
+            Vec3::new(1., 1., 1.),
+        );
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        let hits = (0..1000)
+            .filter(|_| matches!(fog.hit(&ray, 0.001, f32::INFINITY), Intersection::Hit(_)))
+            .count();
+        hits as f32 / 1000.
+    }
+
+    #[test]
+    fn dense_fog_scatters_almost_every_ray() {
+        assert!(scattered_fraction(100.) > 0.99);
+    }
+
+    #[test]
+    fn thin_fog_lets_most_rays_through() {
+        assert!(scattered_fraction(1e-3) < 0.05);
+    }
+}
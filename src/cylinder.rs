@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+// Open-ended cylinder rising height units from base along axis.
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    pub base: Vec3,
+    pub axis: Vec3,
+    pub radius: f32,
+    pub height: f32,
+    pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let axis = self.axis.unit();
+
+        // Solve against the infinite cylinder using the parts of the ray
+        // perpendicular to the axis.
+        let oc = ray.origin - self.base;
+        let d_perp = ray.dir.reject_from(axis);
+        let oc_perp = oc.reject_from(axis);
+        let a = d_perp.len2();
+        let half_b = oc_perp.dot(d_perp);
+        let c = oc_perp.len2() - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+
+        // Ray runs parallel to the axis or misses the tube.
+        if a < 1e-8 || discriminant < 0. {
+            return Intersection::Missed;
+        }
+
+        let sqrtd = discriminant.sqrt();
+
+        // Nearest root in range whose point lies within the [0, height] band.
+        for root in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
+            if !(t_min..=t_max).contains(&root) {
+                continue;
+            }
+            let p = ray.at(root);
+            let local = p - self.base;
+            let h = local.dot(axis);
+            if !(0. ..=self.height).contains(&h) {
+                continue;
+            }
+
+            let outward_normal = local.reject_from(axis) / self.radius;
+            return Intersection::Hit(Hit::facing(
+                root,
+                p,
+                ray,
+                outward_normal,
+                self.material.as_ref(),
+            ));
+        }
+
+        Intersection::Missed
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let axis = self.axis.unit();
+        let top = self.base + axis * self.height;
+        // Extent of the end discs along each world axis.
+        let disc = |a: f32| self.radius * (1. - a * a).max(0.).sqrt();
+        let pad = Vec3::new(disc(axis.x), disc(axis.y), disc(axis.z));
+        Some(Aabb {
+            min: self.base.min(top) - pad,
+            max: self.base.max(top) + pad,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    // Upright, radius 1, from y = 0 to y = 2, around the z = -5 line.
+    fn upright() -> Cylinder {
+        Cylinder {
+            base: Vec3::new(0., 0., -5.),
+            axis: Vec3::new(0., 1., 0.),
+            radius: 1.,
+            height: 2.,
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    #[test]
+    fn side_hit_has_a_horizontal_normal() {
+        let ray = Ray::new(Vec3::new(0., 1., 0.), Vec3::new(0., 0., -1.));
+        match upright().hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                assert_eq!(h.p, Vec3::new(0., 1., -4.));
+                assert_eq!(h.normal, Vec3::new(0., 0., 1.));
+            }
+            Intersection::Missed => panic!("ray at the side's midpoint missed"),
+        }
+    }
+
+    #[test]
+    fn ray_above_the_top_misses() {
+        let ray = Ray::new(Vec3::new(0., 2.5, 0.), Vec3::new(0., 0., -1.));
+        assert!(matches!(
+            upright().hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+}
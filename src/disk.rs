@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+// Flat disk facing along normal; a non-zero inner_radius makes it an annulus.
+#[derive(Debug, Clone)]
+pub struct Disk {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: f32,
+    pub inner_radius: f32,
+    pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let normal = self.normal.unit();
+        let denom = ray.dir.dot(normal);
+
+        // Ray runs parallel to the disk plane.
+        if denom.abs() < 1e-6 {
+            return Intersection::Missed;
+        }
+
+        let t = (self.center - ray.origin).dot(normal) / denom;
+        if !(t_min..=t_max).contains(&t) {
+            return Intersection::Missed;
+        }
+
+        let p = ray.at(t);
+        let offset = p - self.center;
+        let r = offset.len();
+        if !(self.inner_radius..=self.radius).contains(&r) {
+            return Intersection::Missed;
+        }
+
+        // Polar coordinates in the disk plane.
+        let basis = Onb::from_w(normal);
+        let theta = offset
+            .dot(basis.v)
+            .atan2(offset.dot(basis.u))
+            .rem_euclid(2. * std::f32::consts::PI);
+
+        Intersection::Hit(
+            Hit::facing(t, p, ray, normal, self.material.as_ref())
+                .with_uv(r / self.radius, theta / (2. * std::f32::consts::PI)),
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let n = self.normal.unit();
+        // Extent of the rim along each world axis, padded so axis-aligned
+        // disks don't produce a zero-width box.
+        let extent = |a: f32| self.radius * (1. - a * a).max(0.).sqrt() + 1e-4;
+        let half = Vec3::new(extent(n.x), extent(n.y), extent(n.z));
+        Some(Aabb {
+            min: self.center - half,
+            max: self.center + half,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn disk() -> Disk {
+        Disk {
+            center: Vec3::new(0., 0., -3.),
+            normal: Vec3::new(0., 0., 1.),
+            radius: 1.,
+            inner_radius: 0.,
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    #[test]
+    fn ray_at_the_center_hits() {
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        match disk().hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                assert_eq!(h.p, Vec3::new(0., 0., -3.));
+                assert_eq!(h.u, 0.);
+            }
+            Intersection::Missed => panic!("ray at the disk center missed"),
+        }
+    }
+
+    #[test]
+    fn ray_just_outside_the_radius_misses() {
+        let ray = Ray::new(Vec3::new(0., 1.01, 0.), Vec3::new(0., 0., -1.));
+        assert!(matches!(
+            disk().hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+use crate::vec3::Vec3;
+
+// Weighted average over the neighbors within radius of each pixel, using
+// weight(x, y, nx, ny). The kernel is clipped at the image borders.
+fn convolve(
+    pixels: &[Vec3],
+    width: usize,
+    height: usize,
+    radius: usize,
+    weight: impl Fn(usize, usize, usize, usize) -> f32,
+) -> Vec<Vec3> {
+    assert_eq!(pixels.len(), width * height, "pixel buffer size mismatch");
+
+    let mut out = Vec::with_capacity(pixels.len());
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = Vec3::new(0., 0., 0.);
+            let mut total = 0.;
+            for ny in y.saturating_sub(radius)..=(y + radius).min(height - 1) {
+                for nx in x.saturating_sub(radius)..=(x + radius).min(width - 1) {
+                    let w = weight(x, y, nx, ny);
+                    sum += pixels[ny * width + nx] * w;
+                    total += w;
+                }
+            }
+            out.push(sum / total);
+        }
+    }
+    out
+}
+
+// Averages each pixel with its (2 * radius + 1)^2 neighborhood.
+pub fn box_blur(pixels: &[Vec3], width: usize, height: usize, radius: usize) -> Vec<Vec3> {
+    convolve(pixels, width, height, radius, |_, _, _, _| 1.)
+}
+
+// Edge-preserving blur: neighbors are weighted by a gaussian of their
+// distance and of their color difference, so flat regions smooth out while
+// sharp color edges survive.
+pub fn bilateral_blur(
+    pixels: &[Vec3],
+    width: usize,
+    height: usize,
+    radius: usize,
+    sigma_space: f32,
+    sigma_color: f32,
+) -> Vec<Vec3> {
+    let space = -0.5 / (sigma_space * sigma_space);
+    let color = -0.5 / (sigma_color * sigma_color);
+    convolve(pixels, width, height, radius, |x, y, nx, ny| {
+        let dx = x as f32 - nx as f32;
+        let dy = y as f32 - ny as f32;
+        let dc = (pixels[y * width + x] - pixels[ny * width + nx]).len2();
+        ((dx * dx + dy * dy) * space + dc * color).exp()
+    })
+}
+
+// Reverses the row order in place (top-to-bottom <-> bottom-to-top).
+pub fn flip_vertical(pixels: &mut [Vec3], width: usize, height: usize) {
+    assert_eq!(pixels.len(), width * height, "pixel buffer size mismatch");
+
+    for y in 0..height / 2 {
+        let (top, bottom) = pixels.split_at_mut((height - 1 - y) * width);
+        top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+    }
+}
+
+// Mirrors every row in place.
+pub fn flip_horizontal(pixels: &mut [Vec3], width: usize, height: usize) {
+    assert_eq!(pixels.len(), width * height, "pixel buffer size mismatch");
+
+    for row in pixels.chunks_exact_mut(width.max(1)) {
+        row.reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_blur_keeps_a_uniform_image() {
+        let gray = vec![Vec3::new(0.25, 0.5, 0.75); 5 * 4];
+        for b in box_blur(&gray, 5, 4, 1) {
+            assert!(b.approx_eq(gray[0], 1e-6), "{:?}", b);
+        }
+    }
+
+    #[test]
+    fn box_blur_spreads_a_bright_pixel() {
+        let (width, height) = (5, 5);
+        let mut pixels = vec![Vec3::new(0., 0., 0.); width * height];
+        pixels[2 * width + 2] = Vec3::new(9., 9., 9.);
+        let blurred = box_blur(&pixels, width, height, 1);
+        let at = |x: usize, y: usize| blurred[y * width + x].x;
+        // The 3x3 neighborhood shares the light; pixels further out don't.
+        assert!((at(2, 2) - 1.).abs() < 1e-6);
+        assert!((at(1, 3) - 1.).abs() < 1e-6);
+        assert_eq!(at(0, 0), 0.);
+        assert_eq!(at(4, 2), 0.);
+    }
+
+    #[test]
+    fn flipping_twice_restores_the_image() {
+        let original: Vec<Vec3> = (0..12).map(|k| Vec3::new(k as f32, 0., 0.)).collect();
+        let mut pixels = original.clone();
+        flip_vertical(&mut pixels, 4, 3);
+        flip_vertical(&mut pixels, 4, 3);
+        assert_eq!(pixels, original);
+        flip_horizontal(&mut pixels, 4, 3);
+        flip_horizontal(&mut pixels, 4, 3);
+        assert_eq!(pixels, original);
+    }
+
+    #[test]
+    fn asymmetric_2x2_flips_as_expected() {
+        // a b
+        // c d
+        let (a, b, c, d) = (
+            Vec3::new(1., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            Vec3::new(0., 0., 1.),
+            Vec3::new(1., 1., 1.),
+        );
+        let mut pixels = vec![a, b, c, d];
+        flip_vertical(&mut pixels, 2, 2);
+        assert_eq!(pixels, vec![c, d, a, b]);
+        flip_horizontal(&mut pixels, 2, 2);
+        assert_eq!(pixels, vec![d, c, b, a]);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::rng::random_f32;
+use crate::vec3::{Normalization, Vec3};
+
+pub struct Hit<'a> {
+    pub t: f32,
+    pub p: Vec3,
+    pub normal: Vec3,
+    pub front: bool,
+    pub material: &'a dyn Material,
+    // Surface coordinates for texture lookups.
+    pub u: f32,
+    pub v: f32,
+    // How materials normalize directions when scattering from this hit;
+    // the renderer sets it from RenderConfig::normalization.
+    pub normalization: Normalization,
+}
+
+impl<'a> Hit<'a> {
+    pub fn new(t: f32, p: Vec3, normal: Vec3, front: bool, material: &'a dyn Material) -> Hit<'a> {
+        Hit {
+            t,
+            p,
+            normal,
+            front,
+            material,
+            u: 0.,
+            v: 0.,
+            normalization: Normalization::Exact,
+        }
+    }
+
+    pub fn with_uv(self, u: f32, v: f32) -> Hit<'a> {
+        Hit { u, v, ..self }
+    }
+
+    // Orients the normal against the incoming ray and records which side was hit.
+    pub fn facing(
+        t: f32,
+        p: Vec3,
+        ray: &Ray,
+        outward_normal: Vec3,
+        material: &'a dyn Material,
+    ) -> Hit<'a> {
+        let front = ray.dir.dot(outward_normal) < 0.;
+        let normal = match front {
+            true => outward_normal,
+            false => -outward_normal,
+        };
+        Hit::new(t, p, normal, front, material)
+    }
+}
+
+pub enum Intersection<'a> {
+    Missed,
+    Hit(Hit<'a>),
+}
+
+pub trait Hittable: Send + Sync {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_>;
+
+    // None for unbounded primitives.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    // Density, over directions from origin, of the directions random
+    // generates. Objects that can't be sampled report 0.
+    fn pdf_value(&self, _origin: Vec3, _dir: Vec3) -> f32 {
+        0.
+    }
+
+    // Direction from origin toward a random point on the object.
+    fn random(&self, _origin: Vec3) -> Vec3 {
+        Vec3::new(1., 0., 0.)
+    }
+}
+
+#[derive(Default)]
+pub struct HittableList {
+    pub objects: Vec<Box<dyn Hittable>>,
+}
+
+impl HittableList {
+    pub fn add(&mut self, object: Box<dyn Hittable>) {
+        self.objects.push(object);
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+}
+
+impl Hittable for HittableList {
+    // Nearest hit across all objects.
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let mut closest_so_far = t_max;
+        let mut hit = Intersection::Missed;
+
+        for obj in &self.objects {
+            if let Intersection::Hit(h) = obj.hit(ray, t_min, closest_so_far) {
+                closest_so_far = h.t;
+                hit = Intersection::Hit(h);
+            }
+        }
+
+        hit
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut boxes = self.objects.iter().map(|obj| obj.bounding_box());
+        let first = boxes.next()??;
+        boxes.try_fold(first, |acc, b| Some(Aabb::surrounding(acc, b?)))
+    }
+
+    // Each object is picked with equal probability.
+    fn pdf_value(&self, origin: Vec3, dir: Vec3) -> f32 {
+        match self.objects.is_empty() {
+            true => 0.,
+            false => {
+                let sum: f32 = self.objects.iter().map(|o| o.pdf_value(origin, dir)).sum();
+                sum / self.objects.len() as f32
+            }
+        }
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        match self.objects.is_empty() {
+            true => Vec3::new(1., 0., 0.),
+            false => {
+                let i = (random_f32() * self.objects.len() as f32) as usize;
+                self.objects[i.min(self.objects.len() - 1)].random(origin)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::background::Background;
+    use crate::render::ray_color;
+
+    // Never hit, to show dispatch goes through the trait.
+    struct Nothing;
+
+    impl Hittable for Nothing {
+        fn hit(&self, _ray: &Ray, _t_min: f32, _t_max: f32) -> Intersection<'_> {
+            Intersection::Missed
+        }
+
+        fn bounding_box(&self) -> Option<Aabb> {
+            None
+        }
+    }
+
+    #[test]
+    fn missing_objects_show_the_background() {
+        let mut world = HittableList::default();
+        world.add(Box::new(Nothing));
+        world.add(Box::new(Nothing));
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        assert!(matches!(
+            world.hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+
+        let background = Background::SolidColor(Vec3::new(0.2, 0.4, 0.6));
+        assert_eq!(
+            ray_color(&ray, &world, &background, 8),
+            Vec3::new(0.2, 0.4, 0.6)
+        );
+    }
+
+    #[test]
+    fn closer_of_two_overlapping_spheres_wins() {
+        use crate::material::Lambertian;
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        let material = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+        let sphere = |z: f32| Sphere {
+            center: Vec3::new(0., 0., z),
+            radius: 1.,
+            material: material.clone(),
+        };
+        // The far sphere goes in first, so the search must not stop at it.
+        let mut world = HittableList::default();
+        world.add(Box::new(sphere(-3.5)));
+        world.add(Box::new(sphere(-2.)));
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        match world.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => assert_eq!(h.t, 1.),
+            Intersection::Missed => panic!("ray missed both spheres"),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+mod aabb;
+mod animation;
+mod axis_aligned_box;
+mod background;
+mod bvh;
+mod camera;
+mod composite;
+mod constant_medium;
+mod cylinder;
+mod disk;
+mod filter;
+mod hittable;
+mod material;
+mod obj;
+mod onb;
+mod output;
+mod plane;
+mod quad;
+mod ray;
+mod render;
+mod rng;
+mod scene;
+mod sphere;
+mod texture;
+mod transform;
+mod triangle;
+mod vec3;
+
+pub use aabb::Aabb;
+pub use animation::{render_animation, AnimationConfig, AnimationError};
+pub use axis_aligned_box::AxisAlignedBox;
+pub use background::{sky_color, Background, EnvironmentMap};
+pub use bvh::{bvh_or_list, BvhNode};
+pub use camera::{Camera, Projection};
+pub use composite::{average_images, blend_images, CompositeError};
+pub use constant_medium::ConstantMedium;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use filter::{bilateral_blur, box_blur, flip_horizontal, flip_vertical};
+pub use hittable::{Hit, Hittable, HittableList, Intersection};
+pub use material::{
+    schlick_reflectance, Dielectric, DiffuseLight, Glowing, Isotropic, Lambertian, Material, Metal,
+};
+pub use obj::{load_obj, parse_obj, ObjError};
+pub use output::{to_ppm, write_hdr, write_png, write_ppm, ToneMap};
+pub use plane::Plane;
+pub use quad::Quad;
+pub use ray::Ray;
+pub use render::{
+    add_sample_pass, add_sample_pass_with_seed, normal_color, occlusion, ray_color, render,
+    render_depth, render_pixel, render_sky_matte, render_tiles, render_tiles_with_seed,
+    render_with_ray_count, render_with_seed, resolve, RenderConfig, RenderError, Sampling, Shading,
+    Termination,
+};
+pub use scene::{
+    builtin_scene, load_scene, CameraSpec, MaterialRef, MaterialRegistry, MaterialSpec, ObjectSpec,
+    Scene, SceneWarning,
+};
+pub use sphere::{MovingSphere, Sphere};
+pub use texture::{CheckerTexture, ImageTexture, SolidColor, Texture};
+pub use transform::{RotateY, Translate};
+pub use triangle::Triangle;
+pub use vec3::{
+    perturb, random_cosine_direction, random_in_hemisphere, random_in_unit_disk,
+    random_in_unit_sphere, random_unit_vector, Normalization, Vec3,
+};
@@ -1,361 +1,408 @@
-extern crate rand;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use rsay_tracing::{
+    builtin_scene, bvh_or_list, load_scene, render, render_with_seed, write_hdr, write_png,
+    write_ppm, Background, Camera, EnvironmentMap, Hittable, HittableList, Normalization,
+    RenderConfig, Sampling, SceneWarning, Shading, Termination, ToneMap,
+};
+
+const USAGE: &str = "Usage: rsay-tracing [OPTIONS]
+
+Options:
+  --width <N>          Image width in pixels, at least 2 [default: 300]
+  --height <N>         Image height in pixels, at least 2 [default: width at 16:9]
+  --samples <N>        Samples per pixel [default: 50]
+  --max-depth <N>      Maximum ray bounces [default: 50]
+  --output <PATH>      Output file, .ppm, .png or .hdr [default: output/render.ppm]
+  --scene <PATH>       JSON scene description [default: built-in scene]
+  --env <PATH>         Equirectangular environment map [default: sky gradient]
+  --sampling <MODE>    Pixel sampling, random, stratified, adaptive or edges [default: random]
+  --tolerance <X>      Relative noise target for adaptive sampling [default: 0.05]
+  --edge-threshold <X> Color difference that marks an edge for edges sampling [default: 0.1]
+  --mode <MODE>        Shading, materials, normals or ao [default: materials]
+  --tonemap <MODE>     Highlight handling, clamp or reinhard [default: clamp]
+  --exposure <X>       Tone map by scaling colors by X, then clamping
+  --firefly-clamp <X>  Cap each sample's brightness at X [default: no cap]
+  --threads <N>        Worker threads, 0 for all cores [default: 0]
+  --seed <N>           Seed for reproducible renders [default: random]
+  --frame              Point the camera at the whole scene automatically
+  --roulette           End dim paths early with Russian roulette
+  --fast-normalize     Normalize scattered directions faster but less precisely
+  --min-bounces <N>    Bounces before any path may end [default: 3 with --roulette, else 0]
+  --quiet              Don't report progress on stderr
+  --help               Print this message";
 
-use std::{ops, vec};
-
-#[derive(Debug, Copy, Clone)]
-struct Vec3 {
-    x: f32,
-    y: f32,
-    z: f32,
-}
-
-impl Vec3 {
-    fn new(x: f32, y: f32, z: f32) -> Vec3 {
-        Vec3 { x, y, z }
-    }
-
-    fn random(min: f32, max: f32) -> Vec3 {
-        Vec3 {
-            x: random_double(min, max),
-            y: random_double(min, max),
-            z: random_double(min, max),
-        }
-    }
-
-    fn len2(&self) -> f32 {
-        self.x * self.x + self.y * self.y + self.z * self.z
-    }
-
-    fn len(&self) -> f32 {
-        self.len2().sqrt()
-    }
-
-    fn dot(&self, v: Vec3) -> f32 {
-        self.x * v.x + self.y * v.y + self.z * v.z
-    }
-}
-
-impl ops::Sub for Vec3 {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-        }
-    }
-}
-
-impl ops::Add for Vec3 {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-            z: self.z + other.z,
-        }
-    }
-}
-
-impl ops::Mul<f32> for Vec3 {
-    type Output = Self;
-
-    fn mul(self, f: f32) -> Self {
-        Self {
-            x: self.x * f,
-            y: self.y * f,
-            z: self.z * f,
-        }
-    }
-}
-
-impl ops::Div<f32> for Vec3 {
-    type Output = Self;
-
-    fn div(self, f: f32) -> Self {
-        Self {
-            x: self.x / f,
-            y: self.y / f,
-            z: self.z / f,
-        }
-    }
-}
-
-impl ops::Neg for Vec3 {
-    type Output = Self;
-
-    fn neg(self) -> Self {
-        Self {
-            x: -self.x,
-            y: -self.y,
-            z: -self.z,
-        }
-    }
-}
 #[derive(Debug)]
-struct Camera {
-    lower_left_corner: Vec3,
-    horizontal: Vec3,
-    vertical: Vec3,
-    origin: Vec3,
+struct Options {
+    width: i32,
+    height: Option<i32>,
+    samples: i32,
+    max_depth: i32,
+    output: String,
+    scene: Option<String>,
+    env: Option<String>,
+    sampling: Sampling,
+    tolerance: f32,
+    edge_threshold: f32,
+    shading: Shading,
+    termination: Termination,
+    min_bounces: Option<i32>,
+    tone_map: ToneMap,
+    seed: Option<u64>,
+    firefly_clamp: Option<f32>,
+    normalization: Normalization,
+    threads: Option<usize>,
+    frame: bool,
+    quiet: bool,
 }
 
-impl Camera {
-    fn new() -> Camera {
-        let aspect_ratio = 16.0 / 9.0;
-        let viewport_height = 2.0;
-        let viewport_width = aspect_ratio * viewport_height;
-        let focal_length = 1.0;
-
-        let origin = Vec3::new(0., 0., 0.);
-        let horizontal = Vec3::new(viewport_width, 0.0, 0.0);
-        let vertical = Vec3::new(0.0, viewport_height, 0.0);
-
-        Camera {
-            origin,
-            horizontal,
-            vertical,
-            lower_left_corner: origin
-                - horizontal / 2.
-                - vertical / 2.
-                - Vec3::new(0., 0., focal_length),
-        }
-    }
-
-    fn get_ray(&self, u: f32, v: f32) -> Ray {
-        Ray {
-            origin: self.origin,
-            dir: self.lower_left_corner + self.horizontal * u + self.vertical * v - self.origin,
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            width: 300,
+            height: None,
+            samples: 50,
+            max_depth: 50,
+            output: String::from("output/render.ppm"),
+            scene: None,
+            env: None,
+            sampling: Sampling::Random,
+            tolerance: 0.05,
+            edge_threshold: 0.1,
+            shading: Shading::Materials,
+            termination: Termination::FixedDepth,
+            min_bounces: None,
+            tone_map: ToneMap::Clamp,
+            seed: None,
+            firefly_clamp: None,
+            normalization: Normalization::Exact,
+            threads: None,
+            frame: false,
+            quiet: false,
         }
     }
 }
 
-#[derive(Copy, Clone)]
-struct Ray {
-    origin: Vec3,
-    dir: Vec3,
-}
-
-impl Ray {
-    fn at(&self, t: f32) -> Vec3 {
-        self.origin + self.dir * t
-    }
-}
-
-#[derive(Debug, Copy, Clone)]
-struct Sphere {
-    center: Vec3,
-    radius: f32,
-}
-
-struct Hit {
-    t: f32,
-    p: Vec3,
-    normal: Vec3,
-    front: bool,
-}
-
-impl Hit {
-    fn new(t: f32, p: Vec3, normal: Vec3, front: bool) -> Hit {
-        Hit {
-            t,
-            p,
-            normal,
-            front,
-        }
-    }
-}
-
-enum Intersection {
-    Missed,
-    Hit(Hit),
-}
-
-impl Sphere {
-    fn hit(&self, ray: &Ray) -> Intersection {
-        let oc = ray.origin - self.center;
-        let a = ray.dir.dot(ray.dir);
-        let half_b = oc.dot(ray.dir);
-        let c = oc.dot(oc) - self.radius * self.radius;
-        let discriminant = half_b * half_b - a * c;
-
-        if discriminant < 0. {
-            return Intersection::Missed;
-        }
-
-        let sqrtd = discriminant.sqrt();
-
-        // Find the nearest root that lies in the acceptable range.
-        let mut root = (-half_b - sqrtd) / a;
-        if root < 0. || f32::INFINITY < root {
-            root = (-half_b + sqrtd) / a;
-            if root < 0. || f32::INFINITY < root {
-                return Intersection::Missed;
+// Returns None when --help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut opts = Options::default();
+    // Adaptive limits depend on --samples, and edges on --edge-threshold,
+    // either of which may come later.
+    let mut adaptive = false;
+    let mut edges = false;
+
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--help" | "-h" => return Ok(None),
+            "--quiet" | "-q" => {
+                opts.quiet = true;
+                continue;
             }
+            "--frame" => {
+                opts.frame = true;
+                continue;
+            }
+            "--fast-normalize" => {
+                opts.normalization = Normalization::Fast;
+                continue;
+            }
+            "--roulette" => {
+                opts.termination = Termination::RussianRoulette;
+                continue;
+            }
+            _ => {}
         }
 
-        let p = ray.at(root);
-        let outward_normal: Vec3 = (p - self.center) / self.radius;
-        let front = ray.dir.dot(outward_normal) < 0.;
-        let normal = match front {
-            true => outward_normal,
-            false => -outward_normal,
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+        let number = |min: i32| match value.parse::<i32>() {
+            Ok(n) if n >= min => Ok(n),
+            _ => Err(format!(
+                "invalid value '{}' for {}: expected an integer >= {}",
+                value, flag, min
+            )),
         };
 
-        Intersection::Hit(Hit::new(root, p, normal, front))
-    }
-}
-
-fn unit_vector(v: Vec3) -> Vec3 {
-    // Implement / operator between Vec3 and i32 (len).
-    let len = v.len();
-    Vec3 {
-        x: v.x / len,
-        y: v.y / len,
-        z: v.z / len,
-    }
-}
-
-fn random_in_unit_sphere() -> Vec3 {
-    loop {
-        let p = Vec3::random(-1., 1.);
-        match p.len2() >= 1. {
-            true => continue,
-            false => return p,
+        match flag.as_str() {
+            "--width" => opts.width = number(2)?,
+            "--height" => opts.height = Some(number(2)?),
+            "--samples" => opts.samples = number(1)?,
+            "--max-depth" => opts.max_depth = number(1)?,
+            "--min-bounces" => opts.min_bounces = Some(number(0)?),
+            "--output" => opts.output = value,
+            "--scene" => opts.scene = Some(value),
+            "--env" => opts.env = Some(value),
+            "--seed" => {
+                opts.seed = Some(value.parse::<u64>().map_err(|_| {
+                    format!(
+                        "invalid value '{}' for --seed: expected a non-negative integer",
+                        value
+                    )
+                })?)
+            }
+            "--sampling" => {
+                adaptive = value == "adaptive";
+                edges = value == "edges";
+                opts.sampling = match value.as_str() {
+                    "random" | "adaptive" | "edges" => Sampling::Random,
+                    "stratified" => Sampling::Stratified,
+                    _ => {
+                        return Err(format!(
+                            "invalid value '{}' for --sampling: expected random, stratified, adaptive or edges",
+                            value
+                        ))
+                    }
+                }
+            }
+            "--mode" => {
+                opts.shading = match value.as_str() {
+                    "materials" => Shading::Materials,
+                    "normals" => Shading::Normals,
+                    "ao" => Shading::AmbientOcclusion {
+                        samples: 16,
+                        radius: 1.0,
+                    },
+                    _ => {
+                        return Err(format!(
+                            "invalid value '{}' for --mode: expected materials, normals or ao",
+                            value
+                        ))
+                    }
+                }
+            }
+            "--firefly-clamp" => {
+                opts.firefly_clamp = match value.parse::<f32>() {
+                    Ok(x) if x > 0. => Some(x),
+                    _ => {
+                        return Err(format!(
+                            "invalid value '{}' for --firefly-clamp: expected a positive number",
+                            value
+                        ))
+                    }
+                }
+            }
+            // 0 means all cores, same as leaving it out.
+            "--threads" => {
+                opts.threads = match number(0)? {
+                    0 => None,
+                    n => Some(n as usize),
+                }
+            }
+            "--tonemap" => {
+                opts.tone_map = match value.as_str() {
+                    "clamp" => ToneMap::Clamp,
+                    "reinhard" => ToneMap::Reinhard,
+                    _ => {
+                        return Err(format!(
+                            "invalid value '{}' for --tonemap: expected clamp or reinhard",
+                            value
+                        ))
+                    }
+                }
+            }
+            "--exposure" => {
+                opts.tone_map = match value.parse::<f32>() {
+                    Ok(x) if x > 0. => ToneMap::Exposure(x),
+                    _ => {
+                        return Err(format!(
+                            "invalid value '{}' for --exposure: expected a positive number",
+                            value
+                        ))
+                    }
+                }
+            }
+            "--tolerance" => {
+                opts.tolerance = match value.parse::<f32>() {
+                    Ok(x) if x > 0. => x,
+                    _ => {
+                        return Err(format!(
+                            "invalid value '{}' for --tolerance: expected a positive number",
+                            value
+                        ))
+                    }
+                }
+            }
+            "--edge-threshold" => {
+                opts.edge_threshold = match value.parse::<f32>() {
+                    Ok(x) if x >= 0. && x.is_finite() => x,
+                    _ => {
+                        return Err(format!(
+                        "invalid value '{}' for --edge-threshold: expected a non-negative number",
+                        value
+                    ))
+                    }
+                }
+            }
+            _ => return Err(format!("unknown argument '{}'", flag)),
         }
     }
-}
 
-fn clip(v: f32, min: f32, max: f32) -> f32 {
-    match v {
-        c if c > max => max,
-        c if c < min => min,
-        c => c,
+    // --samples caps the per-pixel budget in adaptive mode.
+    if adaptive {
+        opts.sampling = Sampling::Adaptive {
+            min_samples: opts.samples.min(16),
+            max_samples: opts.samples,
+            tolerance: opts.tolerance,
+        };
+    }
+    if edges {
+        opts.sampling = Sampling::Edges {
+            threshold: opts.edge_threshold,
+        };
     }
-}
-
-fn random_double(min: f32, max: f32) -> f32 {
-    min + (max - min) * rand::random::<f32>()
-}
 
-fn write_color(p: &Vec3, samples_per_pixel: i32) {
-    let scale = 1.0 / samples_per_pixel as f32;
-    println!(
-        "{} {} {}",
-        (clip(p.x * scale, 0., 0.999) * 255.) as i32,
-        (clip(p.y * scale, 0., 0.999) * 255.) as i32,
-        (clip(p.z * scale, 0., 0.999) * 255.) as i32
-    )
+    Ok(Some(opts))
 }
 
-fn ray_color(ray: &Ray, objects: &Vec<Sphere>, depth: i32) -> Vec3 {
-    if depth <= 0 {
-        return Vec3::new(0., 0., 0.);
-    }
+fn main() -> io::Result<()> {
+    let opts = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(opts)) => opts,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
 
-    let mut tnear = f32::INFINITY;
-    let mut hit: Option<Hit> = None;
+    // Image
+    let image_width = opts.width;
+    let image_height = opts
+        .height
+        .unwrap_or(((image_width as f32 * 9.0 / 16.0) as i32).max(2));
+    // The camera follows the output shape so nothing gets stretched.
+    let aspect_ratio = image_width as f32 / image_height as f32;
+    let samples_per_pixel = opts.samples;
+    let max_depth = opts.max_depth;
+    let gamma = 2.0;
+
+    let background = match &opts.env {
+        Some(path) => Background::Environment(Arc::new(
+            EnvironmentMap::new(path).map_err(io::Error::other)?,
+        )),
+        None => Background::sky(),
+    };
 
-    for obj in objects {
-        // println!("{:?}", obj);
-        match obj.hit(ray) {
-            Intersection::Missed => continue,
-            Intersection::Hit(h) => {
-                // eprintln!("t {}", h.t);
-                // eprintln!("s {:?}", obj);
-                if h.t < tnear {
-                    tnear = h.t;
-                    hit = Some(h);
+    let (cam, world, lights) = match &opts.scene {
+        Some(path) => {
+            let scene = load_scene(path)?;
+            if let Err(warnings) = scene.validate() {
+                for w in &warnings {
+                    eprintln!("warning: {}", w);
+                }
+                if warnings.iter().any(SceneWarning::is_error) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "scene failed validation",
+                    ));
                 }
             }
+            let (world, lights) = scene.world_and_lights()?;
+            (scene.camera.build(aspect_ratio), world, lights)
         }
-    }
-
-    match hit {
-        // Object.
-        Some(h) => {
-            let target = h.p + h.normal + random_in_unit_sphere();
-            ray_color(
-                &Ray {
-                    origin: h.p,
-                    dir: target - h.p,
-                },
-                objects,
-                depth - 1,
-            ) * 0.5
-        }
-        // Background.
         None => {
-            let unit_direction = unit_vector(ray.dir);
-            let t = 0.5 * (unit_direction.y + 1.0);
-            Vec3::new(1.0, 1.0, 1.0) * (1.0 - t) + Vec3::new(0.5, 0.7, 1.0) * t
+            let (cam, world) = builtin_scene(aspect_ratio);
+            (cam, world, HittableList::default())
         }
-    }
-}
-
-fn main() {
-    // Image
-    let aspect_ratio = 16.0 / 9.0;
-    let image_width = 300;
-    let image_height = (image_width as f32 / aspect_ratio) as i32;
-    let samples_per_pixel = 50;
-    let max_depth = 50;
-
-    // Camera
-    let cam = Camera::new();
-
-    //
-    // Objects in scene.
-    //
-    let s1 = Sphere {
-        center: Vec3 {
-            x: 9.,
-            y: 0.,
-            z: -10.,
-        },
-        radius: 4.,
     };
-
-    let s2 = Sphere {
-        center: Vec3 {
-            x: 0.,
-            y: 0.,
-            z: -10.,
-        },
-        radius: 4.,
+    // Unbounded scenes (e.g. with planes) keep their own camera.
+    let cam = match (opts.frame, world.bounding_box()) {
+        (true, Some(bbox)) => Camera::frame_scene(bbox, aspect_ratio),
+        _ => cam,
     };
+    let world = bvh_or_list(world);
 
-    let s3 = Sphere {
-        center: Vec3 {
-            x: -9.,
-            y: 0.0,
-            z: -10.,
+    // Render
+    let config = RenderConfig {
+        image_width,
+        image_height,
+        samples_per_pixel,
+        max_depth,
+        camera: &cam,
+        world: world.as_ref(),
+        lights: match lights.objects.is_empty() {
+            true => None,
+            false => Some(&lights),
         },
-        radius: 4.,
+        background,
+        sampling: opts.sampling,
+        shading: opts.shading,
+        termination: opts.termination,
+        min_bounces: opts.min_bounces.unwrap_or(match opts.termination {
+            Termination::RussianRoulette => 3,
+            Termination::FixedDepth => 0,
+        }),
+        firefly_clamp: opts.firefly_clamp,
+        normalization: opts.normalization,
+        threads: opts.threads,
+        quiet: opts.quiet,
+    };
+    let result = match opts.seed {
+        Some(seed) => render_with_seed(seed, config),
+        None => render(config),
+    };
+    let image = match result {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        }
     };
 
-    let objects: Vec<Sphere> = vec![s1, s2, s3];
+    let path = opts.output.as_str();
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("png") => write_png(
+            path,
+            &image,
+            image_width as u32,
+            image_height as u32,
+            samples_per_pixel,
+            gamma,
+            opts.tone_map,
+        )?,
+        Some("hdr") => write_hdr(
+            path,
+            &image,
+            image_width as usize,
+            image_height as usize,
+            samples_per_pixel,
+        )?,
+        _ => write_ppm(
+            path,
+            &image,
+            image_width,
+            image_height,
+            samples_per_pixel,
+            gamma,
+            opts.tone_map,
+        )?,
+    }
+    if !opts.quiet {
+        eprintln!("Done!");
+    }
+    Ok(())
+}
 
-    // Render
-    let mut image: Vec<Vec3> = vec![];
-    for j in (0..image_height).rev() {
-        for i in 0..image_width {
-            let mut color = Vec3::new(0., 0., 0.);
-            for _ in 0..samples_per_pixel {
-                let u = (i as f32 + rand::random::<f32>()) / (image_width - 1) as f32;
-                let v = (j as f32 + rand::random::<f32>()) / (image_height - 1) as f32;
-                let ray = cam.get_ray(u, v);
-                color = color + ray_color(&ray, &objects, max_depth);
-            }
-            image.push(color);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threads(value: &str) -> Option<usize> {
+        let args = vec![String::from("--threads"), String::from(value)];
+        parse_args(args.into_iter()).unwrap().unwrap().threads
     }
 
-    println!("P3\n{} {}\n255\n", image_width, image_height);
-    for p in &image {
-        write_color(p, samples_per_pixel);
+    #[test]
+    fn threads_flag_sets_the_pool_size() {
+        // 0 leaves the choice to rayon; seeded output across thread counts
+        // is covered in tests/render.rs.
+        assert_eq!(threads("0"), None);
+        assert_eq!(threads("1"), Some(1));
+        assert_eq!(threads("4"), Some(4));
     }
-    eprintln!("Done!");
 }
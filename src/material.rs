@@ -0,0 +1,328 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::hittable::Hit;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::rng::random_f32;
+use crate::texture::{SolidColor, Texture};
+use crate::vec3::{clip, perturb_with, random_cosine_direction, random_unit_vector_with, Vec3};
+
+pub trait Material: fmt::Debug + Send + Sync {
+    // Returns the scattered ray and its attenuation, or None if the ray is absorbed.
+    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)>;
+
+    // Light given off by the surface itself.
+    fn emitted(&self) -> Vec3 {
+        Vec3::new(0., 0., 0.)
+    }
+    // Density scatter draws scattered's direction with, for materials whose
+    // directions can be importance sampled (e.g. toward lights). None for
+    // specular materials, whose single direction can't be resampled.
+    fn scattering_pdf(&self, _ray: &Ray, _hit: &Hit, _scattered: &Ray) -> Option<f32> {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub struct DiffuseLight {
+    pub emit: Vec3,
+}
+
+impl Material for DiffuseLight {
+    // Lights don't bounce rays.
+    fn scatter(&self, _ray: &Ray, _hit: &Hit) -> Option<(Ray, Vec3)> {
+        None
+    }
+
+    fn emitted(&self) -> Vec3 {
+        self.emit
+    }
+}
+
+// Wraps another material and adds emit to its own emission, so a surface
+// can glow and still bounce light the way the inner material does.
+#[derive(Debug)]
+pub struct Glowing {
+    pub material: Arc<dyn Material>,
+    pub emit: Vec3,
+}
+
+impl Material for Glowing {
+    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+        self.material.scatter(ray, hit)
+    }
+
+    fn emitted(&self) -> Vec3 {
+        self.material.emitted() + self.emit
+    }
+    fn scattering_pdf(&self, ray: &Ray, hit: &Hit, scattered: &Ray) -> Option<f32> {
+        self.material.scattering_pdf(ray, hit, scattered)
+    }
+}
+
+#[derive(Debug)]
+pub struct Lambertian {
+    pub albedo: Box<dyn Texture>,
+}
+
+impl Lambertian {
+    pub fn new(albedo: Vec3) -> Lambertian {
+        Lambertian {
+            albedo: Box::new(SolidColor(albedo)),
+        }
+    }
+}
+
+impl Material for Lambertian {
+    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+        // Cosine-weighted around the normal. The pdf cos/pi cancels the
+        // Lambertian brdf (albedo/pi) times cos, leaving just the albedo.
+        let dir = Onb::from_w_with(hit.normal, hit.normalization).local(random_cosine_direction());
+
+        let scattered = Ray::new_at_time(hit.p, dir, ray.time);
+        Some((scattered, self.albedo.value(hit.u, hit.v, hit.p)))
+    }
+    fn scattering_pdf(&self, _ray: &Ray, hit: &Hit, scattered: &Ray) -> Option<f32> {
+        let cosine = hit.normal.dot(scattered.dir.normalize(hit.normalization));
+        Some(cosine.max(0.) / std::f32::consts::PI)
+    }
+}
+
+#[derive(Debug)]
+pub struct Metal {
+    albedo: Vec3,
+    fuzz: f32,
+}
+
+impl Metal {
+    pub fn new(albedo: Vec3, fuzz: f32) -> Metal {
+        Metal {
+            albedo,
+            fuzz: clip(fuzz, 0., 1.),
+        }
+    }
+}
+
+impl Material for Metal {
+    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+        let reflected = ray.dir.normalize(hit.normalization).reflect(hit.normal);
+        let dir = perturb_with(reflected, self.fuzz, hit.normalization);
+        let scattered = Ray::new_at_time(hit.p, dir, ray.time);
+
+        // Fuzz can push the reflection below the surface.
+        match scattered.dir.dot(hit.normal) > 0. {
+            true => Some((scattered, self.albedo)),
+            false => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Dielectric {
+    // Index of refraction.
+    pub ir: f32,
+}
+
+// Schlick's approximation for reflectance: r0 = ((1 - n) / (1 + n))^2 at
+// normal incidence (cosine = 1), rising to 1 at grazing angles (cosine = 0).
+pub fn schlick_reflectance(cosine: f32, ref_idx: f32) -> f32 {
+    let r0 = (1. - ref_idx) / (1. + ref_idx);
+    let r0 = r0 * r0;
+    r0 + (1. - r0) * (1. - clip(cosine, 0., 1.)).powi(5)
+}
+
+impl Material for Dielectric {
+    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+        let refraction_ratio = match hit.front {
+            true => 1. / self.ir,
+            false => self.ir,
+        };
+
+        let unit_direction = ray.dir.normalize(hit.normalization);
+        let cos_theta = (-unit_direction).dot(hit.normal).min(1.);
+        let sin_theta = (1. - cos_theta * cos_theta).sqrt();
+
+        let cannot_refract = refraction_ratio * sin_theta > 1.;
+        let dir = match cannot_refract
+            || schlick_reflectance(cos_theta, refraction_ratio) > random_f32()
+        {
+            true => unit_direction.reflect(hit.normal),
+            false => unit_direction.refract(hit.normal, refraction_ratio),
+        };
+
+        let scattered = Ray::new_at_time(hit.p, dir, ray.time);
+        Some((scattered, Vec3::new(1., 1., 1.)))
+    }
+}
+
+// Scatters uniformly in all directions; the phase function of a constant medium.
+#[derive(Debug)]
+pub struct Isotropic {
+    pub albedo: Box<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Vec3) -> Isotropic {
+        Isotropic {
+            albedo: Box::new(SolidColor(albedo)),
+        }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+        let dir = random_unit_vector_with(hit.normalization);
+        let scattered = Ray::new_at_time(hit.p, dir, ray.time);
+        Some((scattered, self.albedo.value(hit.u, hit.v, hit.p)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Absorbs every ray.
+    #[derive(Debug)]
+    struct Black;
+
+    impl Material for Black {
+        fn scatter(&self, _ray: &Ray, _hit: &Hit) -> Option<(Ray, Vec3)> {
+            None
+        }
+    }
+
+    #[test]
+    fn absorbing_material_renders_black() {
+        use crate::background::Background;
+        use crate::hittable::HittableList;
+        use crate::render::ray_color;
+        use crate::sphere::Sphere;
+
+        let mut world = HittableList::default();
+        world.add(Box::new(Sphere {
+            center: Vec3::new(0., 0., -2.),
+            radius: 1.,
+            material: Arc::new(Black),
+        }));
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        assert_eq!(
+            ray_color(&ray, &world, &Background::sky(), 8),
+            Vec3::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn lambertian_scatters_from_the_hit_with_its_albedo() {
+        use crate::rng::reseed;
+
+        reseed(12);
+        let albedo = Vec3::new(0.8, 0.3, 0.1);
+        let material = Lambertian::new(albedo);
+        let normal = Vec3::new(0., 1., 0.);
+        let hit = Hit::new(1., Vec3::new(0.5, -1., 2.), normal, true, &material);
+        let ray = Ray::new(Vec3::new(0.5, 0., 2.), Vec3::new(0., -1., 0.));
+        for _ in 0..100 {
+            let (scattered, attenuation) = material.scatter(&ray, &hit).unwrap();
+            assert_eq!(scattered.origin, hit.p);
+            assert_eq!(attenuation, albedo);
+            assert!(scattered.dir.dot(normal) >= 0.);
+        }
+    }
+
+    #[test]
+    fn polished_metal_reflects_at_the_mirror_angle() {
+        let material = Metal::new(Vec3::new(0.9, 0.9, 0.9), 0.);
+        let normal = Vec3::new(0., 1., 0.);
+        let hit = Hit::new(1., Vec3::new(0., 0., 0.), normal, true, &material);
+        // 45 degrees down onto a floor.
+        let ray = Ray::new(Vec3::new(-1., 1., 0.), Vec3::new(1., -1., 0.));
+        let (scattered, _) = material.scatter(&ray, &hit).unwrap();
+        let expected = Vec3::new(1., 1., 0.).unit();
+        assert!(
+            scattered.dir.approx_eq(expected, 1e-6),
+            "{:?}",
+            scattered.dir
+        );
+    }
+
+    #[test]
+    fn glass_refracts_by_snells_law() {
+        use crate::rng::reseed;
+
+        reseed(14);
+        let glass = Dielectric { ir: 1.5 };
+        let hit = Hit::new(
+            1.,
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            true,
+            &glass,
+        );
+        // 30 degrees off the normal, entering from air.
+        let (sin_i, cos_i) = (30f32.to_radians().sin(), 30f32.to_radians().cos());
+        let ray = Ray::new(Vec3::new(-sin_i, cos_i, 0.), Vec3::new(sin_i, -cos_i, 0.));
+
+        // Schlick reflects a few percent of rays; check those that go through.
+        let refracted = (0..100)
+            .map(|_| glass.scatter(&ray, &hit).unwrap().0.dir)
+            .find(|d| d.y < 0.)
+            .unwrap();
+        let sin_t = refracted.x / refracted.len();
+        assert!((sin_t - sin_i / 1.5).abs() < 1e-5, "{}", sin_t);
+    }
+
+    #[test]
+    fn glass_reflects_totally_past_the_critical_angle() {
+        let glass = Dielectric { ir: 1.5 };
+        // Leaving the glass, whose critical angle is about 41.8 degrees, at 60.
+        let hit = Hit::new(
+            1.,
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., -1., 0.),
+            false,
+            &glass,
+        );
+        let (sin_i, cos_i) = (60f32.to_radians().sin(), 60f32.to_radians().cos());
+        let ray = Ray::new(Vec3::new(-sin_i, -cos_i, 0.), Vec3::new(sin_i, cos_i, 0.));
+        for _ in 0..100 {
+            let (scattered, attenuation) = glass.scatter(&ray, &hit).unwrap();
+            let expected = Vec3::new(sin_i, -cos_i, 0.);
+            assert!(
+                scattered.dir.approx_eq(expected, 1e-6),
+                "{:?}",
+                scattered.dir
+            );
+            assert_eq!(attenuation, Vec3::new(1., 1., 1.));
+        }
+    }
+
+    #[test]
+    fn light_is_seen_even_at_depth_zero() {
+        use crate::background::Background;
+        use crate::hittable::HittableList;
+        use crate::render::ray_color;
+        use crate::sphere::Sphere;
+
+        let emit = Vec3::new(4., 3., 2.);
+        let mut world = HittableList::default();
+        world.add(Box::new(Sphere {
+            center: Vec3::new(0., 0., -2.),
+            radius: 1.,
+            material: Arc::new(DiffuseLight { emit }),
+        }));
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        let black = Background::SolidColor(Vec3::new(0., 0., 0.));
+        assert_eq!(ray_color(&ray, &world, &black, 0), emit);
+    }
+
+    #[test]
+    fn schlick_at_normal_incidence_is_the_base_reflectance() {
+        for &n in &[1.33f32, 1.5, 2.4] {
+            let r0 = ((1. - n) / (1. + n)).powi(2);
+            assert!((schlick_reflectance(1., n) - r0).abs() < 1e-7, "{}", n);
+        }
+        // Grazing incidence reflects everything.
+        assert!((schlick_reflectance(0., 1.5) - 1.).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::Arc;
+
+use crate::material::Material;
+use crate::triangle::Triangle;
+use crate::vec3::Vec3;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    // Malformed content on a 1-based line.
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(e) => write!(f, "{}", e),
+            ObjError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<io::Error> for ObjError {
+    fn from(e: io::Error) -> ObjError {
+        ObjError::Io(e)
+    }
+}
+
+// Loads the triangles of a Wavefront OBJ file, all sharing one material.
+pub fn load_obj(path: &str, material: Arc<dyn Material>) -> Result<Vec<Triangle>, ObjError> {
+    parse_obj(&fs::read_to_string(path)?, material)
+}
+
+// Reads v and f statements; normals, UVs and everything else are ignored.
+// Polygons are split into a fan of triangles around their first vertex.
+pub fn parse_obj(text: &str, material: Arc<dyn Material>) -> Result<Vec<Triangle>, ObjError> {
+    let mut vertices: Vec<Vec3> = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let err = |message: String| ObjError::Parse {
+            line: n + 1,
+            message,
+        };
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let mut coord = || -> Result<f32, ObjError> {
+                    let token = tokens
+                        .next()
+                        .ok_or_else(|| err(String::from("vertex needs 3 coordinates")))?;
+                    token
+                        .parse()
+                        .map_err(|_| err(format!("invalid coordinate '{}'", token)))
+                };
+                vertices.push(Vec3::new(coord()?, coord()?, coord()?));
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| {
+                        // Only the position index before any '/' matters.
+                        let index = token.split('/').next().unwrap_or(token);
+                        let i: i64 = index
+                            .parse()
+                            .map_err(|_| err(format!("invalid index '{}'", token)))?;
+                        // 1-based; negative indices count back from the latest vertex.
+                        let resolved = match i {
+                            i if i > 0 => i - 1,
+                            i if i < 0 => vertices.len() as i64 + i,
+                            _ => return Err(err(String::from("index 0 is invalid"))),
+                        };
+                        vertices
+                            .get(resolved as usize)
+                            .filter(|_| resolved >= 0)
+                            .copied()
+                            .ok_or_else(|| err(format!("index {} out of range", i)))
+                    })
+                    .collect::<Result<Vec<Vec3>, ObjError>>()?;
+
+                if face.len() < 3 {
+                    return Err(err(String::from("face needs at least 3 vertices")));
+                }
+                for k in 1..face.len() - 1 {
+                    triangles.push(Triangle {
+                        v0: face[0],
+                        v1: face[k],
+                        v2: face[k + 1],
+                        material: material.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn two_triangle_quad_parses_with_positive_and_negative_indices() {
+        let text = "\
+# unit quad in the z = 0 plane
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 1
+f 1//1 2//1 3//1
+f -4 -2 -1
+";
+        let material = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+        let triangles = parse_obj(text, material).unwrap();
+        let corners: Vec<[Vec3; 3]> = triangles.iter().map(|t| [t.v0, t.v1, t.v2]).collect();
+        let (a, b, c, d) = (
+            Vec3::new(0., 0., 0.),
+            Vec3::new(1., 0., 0.),
+            Vec3::new(1., 1., 0.),
+            Vec3::new(0., 1., 0.),
+        );
+        assert_eq!(corners, vec![[a, b, c], [a, c, d]]);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::vec3::{Normalization, Vec3};
+
+// Orthonormal basis with w along a given direction.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Onb {
+    pub(crate) u: Vec3,
+    pub(crate) v: Vec3,
+    pub(crate) w: Vec3,
+}
+
+impl Onb {
+    pub(crate) fn from_w(n: Vec3) -> Onb {
+        Onb::from_w_with(n, Normalization::Exact)
+    }
+
+    pub(crate) fn from_w_with(n: Vec3, mode: Normalization) -> Onb {
+        let w = n.normalize(mode);
+        // Any helper axis works as long as it isn't parallel to w.
+        let a = match w.x.abs() > 0.9 {
+            true => Vec3::new(0., 1., 0.),
+            false => Vec3::new(1., 0., 0.),
+        };
+        let v = w.cross(a).normalize(mode);
+        let u = w.cross(v);
+        Onb { u, v, w }
+    }
+
+    // Converts local (u, v, w) coordinates to world space.
+    pub(crate) fn local(&self, a: Vec3) -> Vec3 {
+        self.u * a.x + self.v * a.y + self.w * a.z
+    }
+}
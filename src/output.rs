@@ -0,0 +1,224 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::vec3::Vec3;
+
+// Maps linear radiance into displayable [0,1] before gamma correction.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ToneMap {
+    // Values above 1 are clipped.
+    Clamp,
+    // c / (c + 1): compresses highlights smoothly instead of clipping.
+    Reinhard,
+    // Scales by the exposure, then clips.
+    Exposure(f32),
+}
+
+impl ToneMap {
+    fn apply(&self, c: Vec3) -> Vec3 {
+        match *self {
+            ToneMap::Clamp => c,
+            ToneMap::Reinhard => {
+                let reinhard = |x: f32| x / (x + 1.);
+                Vec3::new(reinhard(c.x), reinhard(c.y), reinhard(c.z))
+            }
+            ToneMap::Exposure(exposure) => c * exposure,
+        }
+    }
+}
+
+// Averages the accumulated samples, tone maps, gamma-corrects, and quantizes
+// to 8-bit RGB.
+fn to_rgb(p: &Vec3, samples_per_pixel: i32, gamma: f32, tone_map: ToneMap) -> [u8; 3] {
+    let scale = 1.0 / samples_per_pixel as f32;
+    let encode = |c: f32| c.powf(1. / gamma);
+    let c = tone_map.apply(*p * scale);
+    let c = Vec3::new(encode(c.x), encode(c.y), encode(c.z)).clamp(0., 0.999);
+    [(c.x * 256.) as u8, (c.y * 256.) as u8, (c.z * 256.) as u8]
+}
+
+// Renders the whole image as a plain-text PPM document.
+pub fn to_ppm(
+    pixels: &[Vec3],
+    image_width: i32,
+    image_height: i32,
+    samples_per_pixel: i32,
+    gamma: f32,
+    tone_map: ToneMap,
+) -> String {
+    let mut out = format!("P3\n{} {}\n255\n", image_width, image_height);
+    for p in pixels {
+        let [r, g, b] = to_rgb(p, samples_per_pixel, gamma, tone_map);
+        // Writing to a String cannot fail.
+        let _ = writeln!(out, "{} {} {}", r, g, b);
+    }
+    out
+}
+
+pub fn write_ppm(
+    path: &str,
+    image: &[Vec3],
+    image_width: i32,
+    image_height: i32,
+    samples_per_pixel: i32,
+    gamma: f32,
+    tone_map: ToneMap,
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(
+        to_ppm(
+            image,
+            image_width,
+            image_height,
+            samples_per_pixel,
+            gamma,
+            tone_map,
+        )
+        .as_bytes(),
+    )?;
+    out.flush()
+}
+
+pub fn write_png(
+    path: &str,
+    pixels: &[Vec3],
+    width: u32,
+    height: u32,
+    samples_per_pixel: i32,
+    gamma: f32,
+    tone_map: ToneMap,
+) -> io::Result<()> {
+    // Pixels are stored top row first, which matches the PNG layout.
+    let mut img = image::RgbImage::new(width, height);
+    for (p, px) in pixels.iter().zip(img.pixels_mut()) {
+        *px = image::Rgb(to_rgb(p, samples_per_pixel, gamma, tone_map));
+    }
+    img.save(path).map_err(io::Error::other)
+}
+
+// Radiance RGBE output: averaged linear radiance with no gamma, tone mapping
+// or clipping, so highlights above 1 survive for later processing.
+pub fn write_hdr(
+    path: &str,
+    pixels: &[Vec3],
+    width: usize,
+    height: usize,
+    samples_per_pixel: i32,
+) -> io::Result<()> {
+    let scale = 1.0 / samples_per_pixel as f32;
+    let data: Vec<image::Rgb<f32>> = pixels
+        .iter()
+        .map(|&p| {
+            let c = p * scale;
+            image::Rgb([c.x, c.y, c.z])
+        })
+        .collect();
+
+    let out = BufWriter::new(File::create(path)?);
+    image::codecs::hdr::HdrEncoder::new(out)
+        .encode(&data, width, height)
+        .map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A path in the system temp dir, unique to this test run.
+    fn temp_path(name: &str) -> String {
+        let file = format!("rsay-tracing-{}-{}", std::process::id(), name);
+        std::env::temp_dir()
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn white_pixel_ppm_is_exact() {
+        let ppm = to_ppm(&[Vec3::new(1., 1., 1.)], 1, 1, 1, 2., ToneMap::Clamp);
+        assert_eq!(ppm, "P3\n1 1\n255\n255 255 255\n");
+    }
+
+    #[test]
+    fn write_ppm_starts_with_the_header() {
+        let path = temp_path("header.ppm");
+        let pixels = vec![Vec3::new(0.5, 0.5, 0.5); 3 * 2];
+        write_ppm(&path, &pixels, 3, 2, 1, 2., ToneMap::Clamp).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(text.starts_with("P3\n3 2\n255\n"), "{:?}", text);
+        assert_eq!(text.lines().count(), 3 + 3 * 2);
+    }
+
+    #[test]
+    fn write_png_round_trips_size_and_corner() {
+        let path = temp_path("corner.png");
+        // Red in the top-left corner, black elsewhere.
+        let mut pixels = vec![Vec3::new(0., 0., 0.); 4 * 4];
+        pixels[0] = Vec3::new(1., 0., 0.);
+        write_png(&path, &pixels, 4, 4, 1, 2., ToneMap::Clamp).unwrap();
+        let img = image::open(&path).unwrap().to_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(img.dimensions(), (4, 4));
+        assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(img.get_pixel(3, 3).0, [0, 0, 0]);
+    }
+
+    #[test]
+    fn gamma_brightens_mid_gray() {
+        let gray = Vec3::new(0.25, 0.25, 0.25);
+        let [linear, _, _] = to_rgb(&gray, 1, 1., ToneMap::Clamp);
+        let [corrected, _, _] = to_rgb(&gray, 1, 2., ToneMap::Clamp);
+        assert_eq!(linear, 64);
+        assert_eq!(corrected, 128);
+    }
+
+    #[test]
+    fn header_parses_with_no_blank_line_before_the_pixels() {
+        let pixels = [Vec3::new(0., 0., 0.), Vec3::new(1., 1., 1.)];
+        let ppm = to_ppm(&pixels, 2, 1, 1, 2., ToneMap::Clamp);
+        let mut lines = ppm.split('\n');
+        assert_eq!(lines.next(), Some("P3"));
+        let size: Vec<i32> = lines
+            .next()
+            .unwrap()
+            .split(' ')
+            .map(|n| n.parse().unwrap())
+            .collect();
+        assert_eq!(size, vec![2, 1]);
+        assert_eq!(lines.next(), Some("255"));
+        assert_eq!(lines.next(), Some("0 0 0"));
+        assert_eq!(lines.next(), Some("255 255 255"));
+    }
+
+    #[test]
+    fn bright_colors_compress_under_reinhard_and_clip_under_clamp() {
+        let bright = Vec3::new(10., 10., 10.);
+        let reinhard = ToneMap::Reinhard.apply(bright);
+        assert!(reinhard.max_component() < 1., "{:?}", reinhard);
+        assert!(to_rgb(&bright, 1, 2., ToneMap::Reinhard)[0] < 255);
+        assert_eq!(to_rgb(&bright, 1, 2., ToneMap::Clamp), [255, 255, 255]);
+    }
+
+    #[test]
+    fn write_hdr_keeps_values_above_one() {
+        let path = temp_path("bright.hdr");
+        // Two samples' worth of accumulated color, averaging to (6, 0.5, 3).
+        let pixels = vec![Vec3::new(12., 1., 6.); 2 * 2];
+        write_hdr(&path, &pixels, 2, 2, 2).unwrap();
+        // Decoded directly, since image::open clips HDR files to 8 bits.
+        let reader = io::BufReader::new(File::open(&path).unwrap());
+        let decoder = image::codecs::hdr::HdrDecoder::new(reader).unwrap();
+        let meta = decoder.metadata();
+        let data = decoder.read_image_hdr().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((meta.width, meta.height), (2, 2));
+        let [r, g, b] = data[3].0;
+        // RGBE keeps about 8 bits of mantissa.
+        assert!((r - 6.).abs() < 0.05, "{}", r);
+        assert!((g - 0.5).abs() < 0.05, "{}", g);
+        assert!((b - 3.).abs() < 0.05, "{}", b);
+    }
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+#[derive(Debug, Clone)]
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let denom = ray.dir.dot(self.normal);
+
+        // Ray runs parallel to the plane.
+        if denom.abs() < 1e-6 {
+            return Intersection::Missed;
+        }
+
+        let t = (self.point - ray.origin).dot(self.normal) / denom;
+        if !(t_min..=t_max).contains(&t) {
+            return Intersection::Missed;
+        }
+
+        Intersection::Hit(Hit::facing(
+            t,
+            ray.at(t),
+            ray,
+            self.normal.unit(),
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn ray_straight_down_hits_the_ground() {
+        let ground = Plane {
+            point: Vec3::new(0., -1., 0.),
+            normal: Vec3::new(0., 1., 0.),
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        };
+        let ray = Ray::new(Vec3::new(2., 3., -4.), Vec3::new(0., -1., 0.));
+        match ground.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                assert_eq!(h.t, 4.);
+                assert_eq!(h.p, Vec3::new(2., -1., -4.));
+                assert_eq!(h.normal, Vec3::new(0., 1., 0.));
+                assert!(h.front);
+            }
+            Intersection::Missed => panic!("ray straight down missed the plane"),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+// Parallelogram spanned by the edges u and v from the corner at origin.
+#[derive(Debug, Clone)]
+pub struct Quad {
+    pub origin: Vec3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let n = self.u.cross(self.v);
+        let normal = n.unit();
+        let denom = ray.dir.dot(normal);
+
+        // Ray runs parallel to the quad plane.
+        if denom.abs() < 1e-6 {
+            return Intersection::Missed;
+        }
+
+        let t = (self.origin - ray.origin).dot(normal) / denom;
+        if !(t_min..=t_max).contains(&t) {
+            return Intersection::Missed;
+        }
+
+        // Planar coordinates of the hit point along u and v.
+        let p = ray.at(t);
+        let planar = p - self.origin;
+        let w = n / n.dot(n);
+        let alpha = w.dot(planar.cross(self.v));
+        let beta = w.dot(self.u.cross(planar));
+        if !(0. ..=1.).contains(&alpha) || !(0. ..=1.).contains(&beta) {
+            return Intersection::Missed;
+        }
+
+        Intersection::Hit(
+            Hit::facing(t, p, ray, normal, self.material.as_ref()).with_uv(alpha, beta),
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Padded so axis-aligned quads don't produce a zero-width box.
+        let pad = Vec3::new(1e-4, 1e-4, 1e-4);
+        let corners = [
+            self.origin,
+            self.origin + self.u,
+            self.origin + self.v,
+            self.origin + self.u + self.v,
+        ];
+        let min = corners.iter().fold(corners[0], |acc, &c| acc.min(c));
+        let max = corners.iter().fold(corners[0], |acc, &c| acc.max(c));
+        Some(Aabb {
+            min: min - pad,
+            max: max + pad,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    // 2 x 1 quad in the z = -3 plane, from (-1, -0.5) to (1, 0.5).
+    fn quad() -> Quad {
+        Quad {
+            origin: Vec3::new(-1., -0.5, -3.),
+            u: Vec3::new(2., 0., 0.),
+            v: Vec3::new(0., 1., 0.),
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    #[test]
+    fn ray_through_the_center_hits() {
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        match quad().hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                assert_eq!(h.p, Vec3::new(0., 0., -3.));
+                assert_eq!((h.u, h.v), (0.5, 0.5));
+            }
+            Intersection::Missed => panic!("ray through the center missed"),
+        }
+    }
+
+    #[test]
+    fn ray_outside_the_u_edge_misses() {
+        let ray = Ray::new(Vec3::new(1.01, 0., 0.), Vec3::new(0., 0., -1.));
+        assert!(matches!(
+            quad().hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+}
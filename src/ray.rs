@@ -0,0 +1,37 @@
+use crate::vec3::Vec3;
+
+#[derive(Copy, Clone)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub dir: Vec3,
+    pub time: f32,
+}
+
+impl Ray {
+    // A ray at time 0, for scenes without motion blur.
+    pub fn new(origin: Vec3, dir: Vec3) -> Ray {
+        Ray::new_at_time(origin, dir, 0.)
+    }
+
+    pub fn new_at_time(origin: Vec3, dir: Vec3, time: f32) -> Ray {
+        Ray { origin, dir, time }
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.dir * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn at_steps_along_the_direction() {
+        let (o, d) = (Vec3::new(1., -2., 0.5), Vec3::new(0.25, 3., -1.));
+        let ray = Ray::new(o, d);
+        assert_eq!(ray.at(2.0), o + d * 2.0);
+        assert_eq!(ray.at(0.), o);
+        assert_eq!(ray.time, 0.);
+    }
+}
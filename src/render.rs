@@ -0,0 +1,1473 @@
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use rayon::prelude::*;
+
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::ray::Ray;
+use crate::rng::{random_f32, reseed};
+use crate::vec3::{clip, random_in_hemisphere, Normalization, Vec3};
+
+// Roots closer than this are rejected so scattered rays don't re-hit
+// the surface they left (shadow acne).
+const T_MIN: f32 = 0.001;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Termination {
+    // Every path runs until it misses, is absorbed, or hits max_depth.
+    FixedDepth,
+    // After min_bounces, paths survive with probability equal to their
+    // brightest throughput channel; survivors are reweighted to stay unbiased.
+    RussianRoulette,
+}
+
+thread_local! {
+    // Rays cast by this thread so far. Kept per thread so the hot path never
+    // touches shared state, and never reset: work is billed by how much the
+    // count grows while it runs, so rays cast outside a render don't count.
+    static RAYS: Cell<u64> = const { Cell::new(0) };
+}
+
+// world.hit, counted toward the ray throughput report.
+fn cast<'a>(world: &'a dyn Hittable, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'a> {
+    RAYS.with(|rays| rays.set(rays.get() + 1));
+    world.hit(ray, t_min, t_max)
+}
+
+fn ray_count() -> u64 {
+    RAYS.with(|rays| rays.get())
+}
+
+pub fn ray_color(ray: &Ray, world: &dyn Hittable, background: &Background, depth: i32) -> Vec3 {
+    let path = PathOptions {
+        max_depth: depth,
+        min_bounces: 0,
+        termination: Termination::FixedDepth,
+        normalization: Normalization::Exact,
+    };
+    trace(ray, world, background, None, path)
+}
+
+// How trace follows and ends a path; see the RenderConfig fields of the
+// same names.
+#[derive(Debug, Copy, Clone)]
+struct PathOptions {
+    max_depth: i32,
+    min_bounces: i32,
+    termination: Termination,
+    normalization: Normalization,
+}
+
+// Follows the path iteratively, so large depths can't overflow the stack.
+// Neither the depth limit nor roulette can end a path before min_bounces.
+fn trace(
+    ray: &Ray,
+    world: &dyn Hittable,
+    background: &Background,
+    lights: Option<&dyn Hittable>,
+    path: PathOptions,
+) -> Vec3 {
+    let PathOptions {
+        max_depth,
+        min_bounces,
+        termination,
+        normalization,
+    } = path;
+    let max_depth = max_depth.max(min_bounces);
+    let mut ray = *ray;
+    let mut depth = max_depth;
+    let mut color = Vec3::new(0., 0., 0.);
+    // Product of the attenuations along the path so far.
+    let mut throughput = Vec3::new(1., 1., 1.);
+
+    loop {
+        match cast(world, &ray, T_MIN, f32::INFINITY) {
+            // Object. Emitters still contribute once the bounce budget is spent.
+            Intersection::Hit(mut h) => {
+                h.normalization = normalization;
+                color += throughput * h.material.emitted();
+                if depth <= 0 {
+                    return color;
+                }
+                match h.material.scatter(&ray, &h) {
+                    Some((scattered, attenuation)) => {
+                        let (scattered, weight) = match lights {
+                            Some(lights) => sample_lights(&ray, &h, scattered, lights),
+                            None => (scattered, 1.),
+                        };
+                        throughput = throughput * attenuation * weight;
+                        ray = scattered;
+                        depth -= 1;
+                    }
+                    None => return color,
+                }
+            }
+            // Background.
+            Intersection::Missed if depth <= 0 => return color,
+            Intersection::Missed => return color + throughput * background.color(&ray),
+        }
+
+        if termination == Termination::RussianRoulette && max_depth - depth >= min_bounces {
+            let survival = clip(throughput.max_component(), 0., 1.);
+            if random_f32() >= survival {
+                return color;
+            }
+            throughput = throughput / survival;
+        }
+    }
+}
+
+// For materials with a scattering pdf, half the time replaces the material's
+// direction with one toward a random light. Returns the ray and the weight
+// that keeps the estimate unbiased under that 50/50 mixture of pdfs.
+fn sample_lights(ray: &Ray, hit: &Hit, scattered: Ray, lights: &dyn Hittable) -> (Ray, f32) {
+    let material_pdf = |r: &Ray| hit.material.scattering_pdf(ray, hit, r);
+    if material_pdf(&scattered).is_none() {
+        return (scattered, 1.);
+    }
+
+    let scattered = match random_f32() < 0.5 {
+        true => Ray::new_at_time(scattered.origin, lights.random(hit.p), scattered.time),
+        false => scattered,
+    };
+    let scattering = material_pdf(&scattered).unwrap_or(0.);
+    let pdf = 0.5 * scattering + 0.5 * lights.pdf_value(hit.p, scattered.dir);
+    match pdf > 0. {
+        true => (scattered, scattering / pdf),
+        false => (scattered, 0.),
+    }
+}
+
+// Debug shading: maps the surface normal to a color, ignoring materials.
+pub fn normal_color(ray: &Ray, world: &dyn Hittable, background: &Background) -> Vec3 {
+    match cast(world, ray, T_MIN, f32::INFINITY) {
+        Intersection::Hit(h) => (h.normal + Vec3::new(1., 1., 1.)) * 0.5,
+        Intersection::Missed => background.color(ray),
+    }
+}
+
+// Fraction of hemisphere rays around the hit normal that travel radius
+// without hitting anything: 1 is fully open, 0 fully occluded.
+pub fn occlusion(world: &dyn Hittable, hit: &Hit, time: f32, samples: i32, radius: f32) -> f32 {
+    let escaped = (0..samples)
+        .filter(|_| {
+            let probe = Ray::new_at_time(hit.p, random_in_hemisphere(hit.normal), time);
+            // Directions are unit length, so t is the distance travelled.
+            matches!(cast(world, &probe, T_MIN, radius), Intersection::Missed)
+        })
+        .count();
+    escaped as f32 / samples.max(1) as f32
+}
+
+// Grayscale ambient occlusion; misses count as fully open.
+fn occlusion_color(ray: &Ray, world: &dyn Hittable, samples: i32, radius: f32) -> Vec3 {
+    let ao = match cast(world, ray, T_MIN, f32::INFINITY) {
+        Intersection::Hit(h) => occlusion(world, &h, ray.time, samples, radius),
+        Intersection::Missed => 1.,
+    };
+    Vec3::new(ao, ao, ao)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Shading {
+    // Full path tracing through the materials.
+    Materials,
+    // Primary-ray surface normals, for diagnosing geometry.
+    Normals,
+    // Grayscale share of hemisphere probes that escape within radius.
+    AmbientOcclusion { samples: i32, radius: f32 },
+}
+
+// Degenerate scatters can yield NaN or infinite channels, which would poison
+// the whole pixel average; those channels are dropped to zero instead.
+fn finite_or_zero(c: Vec3) -> Vec3 {
+    let channel = |x: f32| match x.is_finite() {
+        true => x,
+        false => 0.,
+    };
+    Vec3::new(channel(c.x), channel(c.y), channel(c.z))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Sampling {
+    // Independent uniform offsets within the pixel.
+    Random,
+    // One jittered sample per cell of an n x n grid, n = floor(sqrt(samples)).
+    Stratified,
+    // Random offsets, but each pixel stops once the 95% confidence interval
+    // of its mean luminance is within tolerance (relative to the mean), after
+    // at least min_samples and at most max_samples.
+    Adaptive {
+        min_samples: i32,
+        max_samples: i32,
+        tolerance: f32,
+    },
+    // Anti-aliasing only at edges: one random sample per pixel first, then
+    // the full samples_per_pixel wherever a pixel's first sample differs
+    // from one of its 4 neighbors by more than threshold in any channel.
+    // Other pixels keep their single sample.
+    Edges {
+        threshold: f32,
+    },
+}
+
+impl Sampling {
+    // Sub-pixel offset in [0,1)^2 for sample s out of samples_per_pixel.
+    fn offset(&self, s: i32, samples_per_pixel: i32) -> (f32, f32) {
+        let n = (samples_per_pixel as f32).sqrt() as i32;
+        match *self {
+            // Leftover samples beyond the n x n grid fall back to random.
+            Sampling::Stratified if s < n * n => (
+                ((s % n) as f32 + random_f32()) / n as f32,
+                ((s / n) as f32 + random_f32()) / n as f32,
+            ),
+            _ => (random_f32(), random_f32()),
+        }
+    }
+}
+
+pub struct RenderConfig<'a> {
+    pub image_width: i32,
+    pub image_height: i32,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub camera: &'a Camera,
+    pub world: &'a dyn Hittable,
+    // Emitters to sample directly from diffuse bounces; None disables it.
+    pub lights: Option<&'a dyn Hittable>,
+    pub background: Background,
+    pub sampling: Sampling,
+    pub shading: Shading,
+    pub termination: Termination,
+    // Bounces every path gets before max_depth or roulette may end it.
+    pub min_bounces: i32,
+    // Samples brighter than this are scaled down to it, trading a little
+    // energy for fewer fireflies; None keeps every sample as is.
+    pub firefly_clamp: Option<f32>,
+    // Exact or fast normalization of scattered directions.
+    pub normalization: Normalization,
+    // Worker threads; None uses rayon's global pool.
+    pub threads: Option<usize>,
+    pub quiet: bool,
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    Dimensions { width: i32, height: i32 },
+    Samples(i32),
+    Depth(i32),
+    TileSize(i32),
+    Buffer { expected: usize, found: usize },
+    AdaptiveRange { min: i32, max: i32 },
+    EdgeThreshold(f32),
+    MinBounces(i32),
+    Pixel { i: i32, j: i32 },
+    Threads(usize),
+    ThreadPool(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::Dimensions { width, height } => write!(
+                f,
+                "image must be at least 2x2 pixels, got {}x{}",
+                width, height
+            ),
+            RenderError::Samples(n) => {
+                write!(f, "samples per pixel must be at least 1, got {}", n)
+            }
+            RenderError::Depth(n) => write!(f, "max depth must be at least 1, got {}", n),
+            RenderError::TileSize(n) => write!(f, "tile size must be at least 1, got {}", n),
+            RenderError::Buffer { expected, found } => write!(
+                f,
+                "accumulator must hold {} pixels, got {}",
+                expected, found
+            ),
+            RenderError::AdaptiveRange { min, max } => write!(
+                f,
+                "adaptive sampling needs 1 <= min samples <= max samples, got {} and {}",
+                min, max
+            ),
+            RenderError::EdgeThreshold(x) => {
+                write!(f, "edge threshold must be a non-negative number, got {}", x)
+            }
+            RenderError::MinBounces(n) => {
+                write!(f, "min bounces must not be negative, got {}", n)
+            }
+            RenderError::Pixel { i, j } => write!(f, "pixel ({}, {}) is outside the image", i, j),
+            RenderError::Threads(n) => write!(f, "thread count must be at least 1, got {}", n),
+            RenderError::ThreadPool(e) => write!(f, "couldn't start render threads: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+// Renders the scene into accumulated (unaveraged) pixel colors, top row first.
+pub fn render(config: RenderConfig) -> Result<Vec<Vec3>, RenderError> {
+    render_seeded(config, None).map(|(image, _)| image)
+}
+
+// Like render, but also returns the number of rays cast, primary and
+// secondary, as the timing report counts them.
+pub fn render_with_ray_count(config: RenderConfig) -> Result<(Vec<Vec3>, u64), RenderError> {
+    render_seeded(config, None)
+}
+
+// Like render, but every pixel draws from a generator derived from seed, so
+// identical seeds give identical pixel buffers.
+pub fn render_with_seed(seed: u64, config: RenderConfig) -> Result<Vec<Vec3>, RenderError> {
+    render_seeded(config, Some(seed)).map(|(image, _)| image)
+}
+
+// Like render, but splits the image into tile_size x tile_size tiles that
+// are rendered independently. The pixel buffer matches render's layout.
+pub fn render_tiles(config: RenderConfig, tile_size: i32) -> Result<Vec<Vec3>, RenderError> {
+    render_tiles_seeded(config, tile_size, None)
+}
+
+// Tiled counterpart of render_with_seed; gives the same pixels for the same seed.
+pub fn render_tiles_with_seed(
+    seed: u64,
+    config: RenderConfig,
+    tile_size: i32,
+) -> Result<Vec<Vec3>, RenderError> {
+    render_tiles_seeded(config, tile_size, Some(seed))
+}
+
+// Calls f with the one unjittered primary ray through each pixel, top row
+// first, for the single-sample passes below.
+fn per_primary_ray<T: Send>(
+    camera: &Camera,
+    image_width: i32,
+    image_height: i32,
+    f: impl Fn(&Ray) -> T + Sync,
+) -> Vec<T> {
+    // Guards the (size - 1) normalization for single-pixel images.
+    let (w, h) = ((image_width - 1).max(1), (image_height - 1).max(1));
+    let f = &f;
+    (0..image_height)
+        .into_par_iter()
+        .rev()
+        .flat_map_iter(|j| {
+            (0..image_width)
+                .map(move |i| f(&camera.get_ray(i as f32 / w as f32, j as f32 / h as f32)))
+        })
+        .collect()
+}
+
+// Depth pass: the hit t of one primary ray through each pixel, top
+// row first. Pixels that see only background hold f32::INFINITY.
+pub fn render_depth(
+    camera: &Camera,
+    world: &dyn Hittable,
+    image_width: i32,
+    image_height: i32,
+) -> Vec<f32> {
+    per_primary_ray(camera, image_width, image_height, |ray| {
+        match world.hit(ray, T_MIN, f32::INFINITY) {
+            Intersection::Hit(hit) => hit.t,
+            Intersection::Missed => f32::INFINITY,
+        }
+    })
+}
+
+// Sky matte: the background color for primary rays that escape the scene
+// and black wherever geometry blocks it, top row first.
+pub fn render_sky_matte(
+    camera: &Camera,
+    world: &dyn Hittable,
+    background: &Background,
+    image_width: i32,
+    image_height: i32,
+) -> Vec<Vec3> {
+    per_primary_ray(camera, image_width, image_height, |ray| {
+        match world.hit(ray, T_MIN, f32::INFINITY) {
+            Intersection::Hit(_) => Vec3::new(0., 0., 0.),
+            Intersection::Missed => background.color(ray),
+        }
+    })
+}
+
+// Runs f on a dedicated pool of the given size, or rayon's global pool for
+// None. Seeded renders come out the same either way, since every sample
+// reseeds the generator of whichever worker thread picks it up.
+fn in_pool<T: Send>(
+    threads: Option<usize>,
+    f: impl FnOnce() -> T + Send,
+) -> Result<T, RenderError> {
+    match threads {
+        None => Ok(f()),
+        Some(0) => Err(RenderError::Threads(0)),
+        Some(n) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map_err(|e| RenderError::ThreadPool(e.to_string()))?;
+            Ok(pool.install(f))
+        }
+    }
+}
+
+fn validate(config: &RenderConfig) -> Result<(), RenderError> {
+    // Pixel coordinates are normalized by (size - 1).
+    if config.image_width < 2 || config.image_height < 2 {
+        return Err(RenderError::Dimensions {
+            width: config.image_width,
+            height: config.image_height,
+        });
+    }
+    if config.samples_per_pixel < 1 {
+        return Err(RenderError::Samples(config.samples_per_pixel));
+    }
+    if config.max_depth < 1 {
+        return Err(RenderError::Depth(config.max_depth));
+    }
+    if config.min_bounces < 0 {
+        return Err(RenderError::MinBounces(config.min_bounces));
+    }
+    if let Sampling::Adaptive {
+        min_samples,
+        max_samples,
+        ..
+    } = config.sampling
+    {
+        if min_samples < 1 || max_samples < min_samples {
+            return Err(RenderError::AdaptiveRange {
+                min: min_samples,
+                max: max_samples,
+            });
+        }
+    }
+    if let Sampling::Edges { threshold } = config.sampling {
+        if !(threshold >= 0. && threshold.is_finite()) {
+            return Err(RenderError::EdgeThreshold(threshold));
+        }
+    }
+    Ok(())
+}
+
+// Averaged color of the single pixel (i, j), j counted from the bottom row,
+// exactly as render_with_seed would produce it for the same seed. Handy for
+// debugging one ray path without rendering the whole image.
+pub fn render_pixel(config: &RenderConfig, i: i32, j: i32, seed: u64) -> Result<Vec3, RenderError> {
+    validate(config)?;
+    if !(0..config.image_width).contains(&i) || !(0..config.image_height).contains(&j) {
+        return Err(RenderError::Pixel { i, j });
+    }
+    let color = match config.sampling {
+        Sampling::Edges { threshold } => {
+            let first = |i, j| render_sample(config, i, j, 0, Some(seed));
+            let neighbors = neighbors(config, i, j).map(|(ni, nj)| first(ni, nj));
+            edge_pixel(config, i, j, Some(seed), first(i, j), neighbors, threshold)
+        }
+        _ => accumulate_pixel(config, i, j, Some(seed)),
+    };
+    Ok(color / config.samples_per_pixel as f32)
+}
+
+// Accumulated color of pixel (i, j), with j counted from the bottom row.
+fn accumulate_pixel(config: &RenderConfig, i: i32, j: i32, seed: Option<u64>) -> Vec3 {
+    if let Sampling::Adaptive {
+        min_samples,
+        max_samples,
+        tolerance,
+    } = config.sampling
+    {
+        return adaptive_pixel(config, i, j, seed, min_samples, max_samples, tolerance).0;
+    }
+
+    let mut color = Vec3::new(0., 0., 0.);
+    for s in 0..config.samples_per_pixel {
+        color += render_sample(config, i, j, s, seed);
+    }
+    color
+}
+
+// Accumulated color of pixel (i, j) under Sampling::Edges, given its first
+// sample and its neighbors'. Flat pixels are scaled up by samples_per_pixel
+// so they average like the fully sampled ones.
+fn edge_pixel(
+    config: &RenderConfig,
+    i: i32,
+    j: i32,
+    seed: Option<u64>,
+    first: Vec3,
+    mut neighbors: impl Iterator<Item = Vec3>,
+    threshold: f32,
+) -> Vec3 {
+    match neighbors.any(|n| (n - first).abs().max_component() > threshold) {
+        true => accumulate_pixel(config, i, j, seed),
+        false => first * config.samples_per_pixel as f32,
+    }
+}
+
+// The in-image pixels sharing a side with (i, j).
+fn neighbors(config: &RenderConfig, i: i32, j: i32) -> impl Iterator<Item = (i32, i32)> {
+    let (width, height) = (config.image_width, config.image_height);
+    IntoIterator::into_iter([(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)])
+        .filter(move |&(ni, nj)| (0..width).contains(&ni) && (0..height).contains(&nj))
+}
+
+// First pass of Sampling::Edges over the whole image, top row first.
+fn first_pass(config: &RenderConfig, seed: Option<u64>, progress: &Progress) -> Vec<Vec3> {
+    (0..config.image_height)
+        .into_par_iter()
+        .rev()
+        .flat_map_iter(|j| {
+            progress.count_rays(|| {
+                (0..config.image_width)
+                    .map(|i| render_sample(config, i, j, 0, seed))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect()
+}
+
+// Accumulated color of pixel (i, j), picking between the samplers.
+// first holds the edge pass from first_pass when sampling by edges.
+fn sample_pixel(
+    config: &RenderConfig,
+    i: i32,
+    j: i32,
+    seed: Option<u64>,
+    first: Option<&[Vec3]>,
+) -> Vec3 {
+    match (config.sampling, first) {
+        (Sampling::Edges { threshold }, Some(first)) => {
+            let (width, height) = (config.image_width, config.image_height);
+            let at = |i: i32, j: i32| first[((height - 1 - j) * width + i) as usize];
+            let neighbors = neighbors(config, i, j).map(|(ni, nj)| at(ni, nj));
+            edge_pixel(config, i, j, seed, at(i, j), neighbors, threshold)
+        }
+        _ => accumulate_pixel(config, i, j, seed),
+    }
+}
+
+// Samples until the pixel converges. The mean is scaled back up by
+// samples_per_pixel so the result averages like any other accumulated pixel.
+// Also returns how many samples the pixel took.
+fn adaptive_pixel(
+    config: &RenderConfig,
+    i: i32,
+    j: i32,
+    seed: Option<u64>,
+    min_samples: i32,
+    max_samples: i32,
+    tolerance: f32,
+) -> (Vec3, i32) {
+    let mut sum = Vec3::new(0., 0., 0.);
+    // Welford's running mean and variance of the sample luminance.
+    let (mut mean, mut m2) = (0., 0.);
+    let mut n = 0;
+
+    while n < max_samples {
+        let c = render_sample(config, i, j, n, seed);
+        sum += c;
+        n += 1;
+
+        let luminance = luminance(c);
+        let delta = luminance - mean;
+        mean += delta / n as f32;
+        m2 += delta * (luminance - mean);
+
+        if n >= min_samples.max(2) {
+            let variance = m2 / (n - 1) as f32;
+            let interval = 1.96 * (variance / n as f32).sqrt();
+            if interval <= tolerance * mean {
+                break;
+            }
+        }
+    }
+
+    (sum * (config.samples_per_pixel as f32 / n as f32), n)
+}
+
+// Color of sample s of pixel (i, j). Seeded samples each draw from their
+// own stream, so a sample comes out the same however it's scheduled.
+fn render_sample(config: &RenderConfig, i: i32, j: i32, s: i32, seed: Option<u64>) -> Vec3 {
+    if let Some(seed) = seed {
+        let pixel = (j * config.image_width + i) as u64;
+        reseed(
+            seed ^ pixel.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (s as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9),
+        );
+    }
+    let (du, dv) = config.sampling.offset(s, config.samples_per_pixel);
+    let u = (i as f32 + du) / (config.image_width - 1) as f32;
+    let v = (j as f32 + dv) / (config.image_height - 1) as f32;
+    let ray = config.camera.get_ray(u, v);
+    let sample = match config.shading {
+        Shading::Materials => {
+            let path = PathOptions {
+                max_depth: config.max_depth,
+                min_bounces: config.min_bounces,
+                termination: config.termination,
+                normalization: config.normalization,
+            };
+            trace(&ray, config.world, &config.background, config.lights, path)
+        }
+        Shading::Normals => normal_color(&ray, config.world, &config.background),
+        Shading::AmbientOcclusion { samples, radius } => {
+            occlusion_color(&ray, config.world, samples, radius)
+        }
+    };
+    let sample = finite_or_zero(sample);
+    match config.firefly_clamp {
+        Some(max) if luminance(sample) > max => sample * (max / luminance(sample)),
+        _ => sample,
+    }
+}
+
+// Brightness of a color as the mean of its channels.
+fn luminance(c: Vec3) -> f32 {
+    (c.x + c.y + c.z) / 3.
+}
+
+// Progressive rendering: adds one more sample to every pixel of accum (top
+// row first) and bumps count. samples_per_pixel only sizes the stratified
+// grid here; call resolve to get displayable averages at any point.
+pub fn add_sample_pass(
+    accum: &mut [Vec3],
+    count: &mut u32,
+    config: &RenderConfig,
+) -> Result<(), RenderError> {
+    add_sample_pass_seeded(accum, count, config, None)
+}
+
+// Like add_sample_pass, but pass n draws the same sample n that
+// render_with_seed takes for each pixel, so n passes sum to an n-sample
+// seeded render.
+pub fn add_sample_pass_with_seed(
+    seed: u64,
+    accum: &mut [Vec3],
+    count: &mut u32,
+    config: &RenderConfig,
+) -> Result<(), RenderError> {
+    add_sample_pass_seeded(accum, count, config, Some(seed))
+}
+
+fn add_sample_pass_seeded(
+    accum: &mut [Vec3],
+    count: &mut u32,
+    config: &RenderConfig,
+    seed: Option<u64>,
+) -> Result<(), RenderError> {
+    validate(config)?;
+    let expected = (config.image_width * config.image_height) as usize;
+    if accum.len() != expected {
+        return Err(RenderError::Buffer {
+            expected,
+            found: accum.len(),
+        });
+    }
+
+    let s = *count as i32;
+    in_pool(config.threads, || {
+        accum
+            .par_chunks_mut(config.image_width as usize)
+            .enumerate()
+            .for_each(|(row, pixels)| {
+                let j = config.image_height - 1 - row as i32;
+                for (i, p) in pixels.iter_mut().enumerate() {
+                    *p += render_sample(config, i as i32, j, s, seed);
+                }
+            })
+    })?;
+    *count += 1;
+    Ok(())
+}
+
+// Averages an accumulator buffer over the passes added so far.
+pub fn resolve(accum: &[Vec3], count: u32) -> Vec<Vec3> {
+    let scale = 1. / count.max(1) as f32;
+    accum.iter().map(|&p| p * scale).collect()
+}
+
+// The image and the number of rays it took.
+fn render_seeded(config: RenderConfig, seed: Option<u64>) -> Result<(Vec<Vec3>, u64), RenderError> {
+    validate(&config)?;
+
+    let progress = Progress::new(config.image_height as usize, "Scanlines", config.quiet);
+    // Scanlines are rendered in parallel; collect keeps them top row first.
+    let image: Vec<Vec3> = in_pool(config.threads, || {
+        let first = match config.sampling {
+            Sampling::Edges { .. } => Some(first_pass(&config, seed, &progress)),
+            _ => None,
+        };
+        (0..config.image_height)
+            .into_par_iter()
+            .rev()
+            .flat_map_iter(|j| {
+                let row: Vec<Vec3> = progress.count_rays(|| {
+                    (0..config.image_width)
+                        .map(|i| sample_pixel(&config, i, j, seed, first.as_deref()))
+                        .collect()
+                });
+                progress.tick();
+                row
+            })
+            .collect()
+    })?;
+    let rays = progress.finish();
+
+    Ok((image, rays))
+}
+
+fn render_tiles_seeded(
+    config: RenderConfig,
+    tile_size: i32,
+    seed: Option<u64>,
+) -> Result<Vec<Vec3>, RenderError> {
+    validate(&config)?;
+    if tile_size < 1 {
+        return Err(RenderError::TileSize(tile_size));
+    }
+
+    let (width, height) = (config.image_width, config.image_height);
+    let tiles_x = (width + tile_size - 1) / tile_size;
+    let tiles_y = (height + tile_size - 1) / tile_size;
+
+    let progress = Progress::new((tiles_x * tiles_y) as usize, "Tiles", config.quiet);
+    // Rayon hands tiles out to idle threads, so uneven tiles balance out.
+    let tiles: Vec<(i32, i32, Vec<Vec3>)> = in_pool(config.threads, || {
+        // Edge detection looks across tile borders, so the first pass
+        // covers the whole image up front.
+        let first = match config.sampling {
+            Sampling::Edges { .. } => Some(first_pass(&config, seed, &progress)),
+            _ => None,
+        };
+        (0..tiles_x * tiles_y)
+            .into_par_iter()
+            .map(|t| {
+                let x0 = (t % tiles_x) * tile_size;
+                let y0 = (t / tiles_x) * tile_size;
+                let mut pixels = Vec::new();
+                progress.count_rays(|| {
+                    for j in y0..(y0 + tile_size).min(height) {
+                        for i in x0..(x0 + tile_size).min(width) {
+                            pixels.push(sample_pixel(&config, i, j, seed, first.as_deref()));
+                        }
+                    }
+                });
+                progress.tick();
+                (x0, y0, pixels)
+            })
+            .collect()
+    })?;
+    progress.finish();
+
+    // Scatter the tiles into the top-row-first buffer.
+    let mut image = vec![Vec3::new(0., 0., 0.); (width * height) as usize];
+    for (x0, y0, pixels) in tiles {
+        let tile_width = (x0 + tile_size).min(width) - x0;
+        for (k, color) in pixels.into_iter().enumerate() {
+            let i = x0 + k as i32 % tile_width;
+            let j = y0 + k as i32 / tile_width;
+            image[((height - 1 - j) * width + i) as usize] = color;
+        }
+    }
+
+    Ok(image)
+}
+
+// Reports work units (scanlines or tiles) remaining on stderr, then the
+// render time and ray throughput.
+struct Progress {
+    total: usize,
+    label: &'static str,
+    done: AtomicUsize,
+    rays: AtomicU64,
+    start: Instant,
+    quiet: bool,
+}
+
+impl Progress {
+    fn new(total: usize, label: &'static str, quiet: bool) -> Progress {
+        Progress {
+            total,
+            label,
+            done: AtomicUsize::new(0),
+            rays: AtomicU64::new(0),
+            start: Instant::now(),
+            quiet,
+        }
+    }
+
+    // Runs f, adding the rays it casts on this thread to the report.
+    fn count_rays<T>(&self, f: impl FnOnce() -> T) -> T {
+        let before = ray_count();
+        let result = f();
+        self.rays.fetch_add(ray_count() - before, Ordering::Relaxed);
+        result
+    }
+
+    // Marks one more unit done, and returns how many are left.
+    fn tick(&self) -> usize {
+        let remaining = self.total - (self.done.fetch_add(1, Ordering::Relaxed) + 1);
+        if !self.quiet {
+            eprint!("\r{} remaining: {} ", self.label, remaining);
+        }
+        remaining
+    }
+
+    // Reports the time and throughput, and returns the rays cast.
+    fn finish(&self) -> u64 {
+        let rays = self.rays.load(Ordering::Relaxed);
+        if !self.quiet {
+            let secs = self.start.elapsed().as_secs_f64();
+            eprintln!(
+                "\nRendered in {:.1}s ({:.1}M rays/s)",
+                secs,
+                rays as f64 / secs.max(1e-9) / 1e6
+            );
+        }
+        rays
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable::HittableList;
+    use crate::scene::builtin_scene;
+
+    fn config<'a>(camera: &'a Camera, world: &'a HittableList) -> RenderConfig<'a> {
+        RenderConfig {
+            image_width: 8,
+            image_height: 6,
+            samples_per_pixel: 4,
+            max_depth: 8,
+            camera,
+            world,
+            lights: None,
+            background: Background::sky(),
+            sampling: Sampling::Random,
+            shading: Shading::Materials,
+            termination: Termination::FixedDepth,
+            min_bounces: 0,
+            firefly_clamp: None,
+            normalization: Normalization::Exact,
+            threads: None,
+            quiet: true,
+        }
+    }
+
+    #[test]
+    fn zero_samples_is_an_error() {
+        let (camera, world) = builtin_scene(8. / 6.);
+        let result = render(RenderConfig {
+            samples_per_pixel: 0,
+            ..config(&camera, &world)
+        });
+        assert!(matches!(result, Err(RenderError::Samples(0))));
+    }
+
+    #[test]
+    fn zero_height_is_an_error() {
+        let (camera, world) = builtin_scene(8. / 6.);
+        let result = render(RenderConfig {
+            image_height: 0,
+            ..config(&camera, &world)
+        });
+        assert!(matches!(
+            result,
+            Err(RenderError::Dimensions {
+                width: 8,
+                height: 0
+            })
+        ));
+    }
+
+    // The recursive ray_color that trace replaced, kept as a reference.
+    fn recursive_color(
+        ray: &Ray,
+        world: &dyn Hittable,
+        background: &Background,
+        depth: i32,
+    ) -> Vec3 {
+        match world.hit(ray, T_MIN, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                let emitted = h.material.emitted();
+                if depth <= 0 {
+                    return emitted;
+                }
+                match h.material.scatter(ray, &h) {
+                    Some((scattered, attenuation)) => {
+                        emitted
+                            + attenuation
+                                * recursive_color(&scattered, world, background, depth - 1)
+                    }
+                    None => emitted,
+                }
+            }
+            Intersection::Missed if depth <= 0 => Vec3::new(0., 0., 0.),
+            Intersection::Missed => background.color(ray),
+        }
+    }
+
+    #[test]
+    fn iterative_matches_recursive() {
+        let (camera, world) = builtin_scene(16. / 9.);
+        let background = Background::sky();
+        for k in 0..200 {
+            reseed(k);
+            let ray = camera.get_ray(random_f32(), random_f32());
+            reseed(k + 1000);
+            let expected = recursive_color(&ray, &world, &background, 10);
+            reseed(k + 1000);
+            let got = ray_color(&ray, &world, &background, 10);
+            assert!(got.approx_eq(expected, 1e-5), "{:?} != {:?}", got, expected);
+        }
+    }
+
+    #[test]
+    fn deep_max_depth_does_not_overflow() {
+        use crate::material::Metal;
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        // A perfect mirror seen from inside never lets the ray escape.
+        let mut world = HittableList::default();
+        world.add(Box::new(Sphere {
+            center: Vec3::new(0., 0., 0.),
+            radius: 1.,
+            material: Arc::new(Metal::new(Vec3::new(1., 1., 1.), 0.)),
+        }));
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0.3, 0.2, 1.));
+        let color = ray_color(&ray, &world, &Background::sky(), 100_000);
+        assert_eq!(color, Vec3::new(0., 0., 0.));
+    }
+
+    // A flat white disc on a flat gray background: only the silhouette
+    // varies within a pixel, and every sample is a single ray.
+    fn disc_scene() -> (Camera, HittableList) {
+        use crate::material::DiffuseLight;
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        let camera = Camera::new(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 0., -1.),
+            Vec3::new(0., 1., 0.),
+            90.,
+            1.,
+            0.,
+            1.,
+        );
+        let mut world = HittableList::default();
+        world.add(Box::new(Sphere {
+            center: Vec3::new(0., 0., -2.),
+            radius: 1.,
+            material: Arc::new(DiffuseLight {
+                emit: Vec3::new(1., 1., 1.),
+            }),
+        }));
+        (camera, world)
+    }
+
+    #[test]
+    fn adaptive_flat_pixels_stop_at_min_samples() {
+        let (camera, world) = disc_scene();
+        let config = RenderConfig {
+            image_width: 24,
+            image_height: 24,
+            samples_per_pixel: 64,
+            background: Background::SolidColor(Vec3::new(0.5, 0.5, 0.5)),
+            ..config(&camera, &world)
+        };
+
+        let counts: Vec<i32> = (0..24)
+            .flat_map(|j| (0..24).map(move |i| (i, j)))
+            .map(|(i, j)| adaptive_pixel(&config, i, j, Some(5), 4, 64, 0.01).1)
+            .collect();
+        // Corner (background) and center (disc) pixels are flat.
+        assert_eq!(counts[0], 4);
+        assert_eq!(counts[12 * 24 + 12], 4);
+        // Pixels on the silhouette keep sampling.
+        assert!(counts.iter().any(|&n| n > 16));
+        let flat = counts.iter().filter(|&&n| n == 4).count();
+        assert!(flat > counts.len() / 2);
+    }
+
+    #[test]
+    fn background_sample_casts_one_ray() {
+        let (camera, _) = builtin_scene(8. / 6.);
+        let world = HittableList::default();
+        let config = config(&camera, &world);
+        let before = ray_count();
+        render_sample(&config, 3, 2, 0, None);
+        assert_eq!(ray_count() - before, 1);
+    }
+
+    #[test]
+    fn ray_count_covers_only_the_render() {
+        let (camera, _) = builtin_scene(1.);
+        let world = HittableList::default();
+        // Rays cast outside a render must not be billed to it.
+        let ray = camera.get_ray(0.5, 0.5);
+        ray_color(&ray, &world, &Background::sky(), 4);
+
+        for sampling in [Sampling::Random, Sampling::Edges { threshold: 0.1 }] {
+            let (_, rays) = render_with_ray_count(RenderConfig {
+                image_width: 2,
+                image_height: 2,
+                samples_per_pixel: 1,
+                sampling,
+                // Flat, so the edge pass finds no edges.
+                background: Background::SolidColor(Vec3::new(0.5, 0.5, 0.5)),
+                threads: Some(1),
+                ..config(&camera, &world)
+            })
+            .unwrap();
+            // One primary ray per pixel; flat pixels in the edge mode keep
+            // their first-pass sample.
+            assert_eq!(rays, 4);
+        }
+    }
+
+    #[test]
+    fn edges_sample_only_the_silhouette() {
+        let (camera, world) = disc_scene();
+        let config = RenderConfig {
+            image_width: 24,
+            image_height: 24,
+            samples_per_pixel: 16,
+            background: Background::SolidColor(Vec3::new(0.5, 0.5, 0.5)),
+            sampling: Sampling::Edges { threshold: 0.1 },
+            ..config(&camera, &world)
+        };
+        let progress = Progress::new(1, "", true);
+        let first = first_pass(&config, Some(5), &progress);
+
+        // Rays each pixel casts beyond its first-pass sample.
+        let extra: Vec<u64> = (0..24)
+            .rev()
+            .flat_map(|j| (0..24).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let before = ray_count();
+                sample_pixel(&config, i, j, Some(5), Some(&first));
+                ray_count() - before
+            })
+            .collect();
+        // Background corner and disc center stay at one sample.
+        assert_eq!(extra[0], 0);
+        assert_eq!(extra[12 * 24 + 12], 0);
+        // Silhouette pixels get the full budget, and they're the minority.
+        assert!(extra.contains(&16));
+        assert!(extra.iter().all(|&n| n == 0 || n == 16));
+        assert!(extra.iter().filter(|&&n| n > 0).count() < extra.len() / 2);
+    }
+
+    #[test]
+    fn materials_see_the_configured_normalization() {
+        use crate::material::Material;
+        use crate::sphere::Sphere;
+        use std::sync::{Arc, Mutex};
+
+        // Absorbs every ray, remembering the mode each hit carried.
+        #[derive(Debug, Default)]
+        struct Recorder {
+            seen: Mutex<Vec<Normalization>>,
+        }
+        impl Material for Recorder {
+            fn scatter(&self, _ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+                self.seen.lock().unwrap().push(hit.normalization);
+                None
+            }
+        }
+
+        let (camera, _) = disc_scene();
+        let recorder = Arc::new(Recorder::default());
+        let mut world = HittableList::default();
+        world.add(Box::new(Sphere {
+            center: Vec3::new(0., 0., -2.),
+            radius: 1.,
+            material: recorder.clone(),
+        }));
+        for &mode in &[Normalization::Exact, Normalization::Fast] {
+            recorder.seen.lock().unwrap().clear();
+            render(RenderConfig {
+                normalization: mode,
+                ..config(&camera, &world)
+            })
+            .unwrap();
+            let seen = recorder.seen.lock().unwrap();
+            assert!(!seen.is_empty());
+            assert!(seen.iter().all(|&m| m == mode), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn sky_matte_shows_sky_overhead_and_masks_geometry() {
+        use crate::background::SKY_TOP;
+
+        let (_, world) = disc_scene();
+        let up = Camera::new(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            Vec3::new(0., 0., -1.),
+            90.,
+            1.,
+            0.,
+            1.,
+        );
+        // The center pixel of a 3x3 image looks straight along the camera axis.
+        let matte = render_sky_matte(&up, &world, &Background::sky(), 3, 3);
+        assert!(matte[4].approx_eq(SKY_TOP, 1e-6), "{:?}", matte[4]);
+
+        let (ahead, _) = disc_scene();
+        let matte = render_sky_matte(&ahead, &world, &Background::sky(), 3, 3);
+        assert_eq!(matte[4], Vec3::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn glowing_sphere_adds_its_emission_to_the_bounce() {
+        use crate::material::{Glowing, Lambertian, Material};
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        let gray: Arc<dyn Material> = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+        let emit = Vec3::new(2., 1., 0.5);
+        let glowing = Arc::new(Glowing {
+            material: gray.clone(),
+            emit,
+        });
+        let color = |material: Arc<dyn Material>| {
+            let mut world = HittableList::default();
+            world.add(Box::new(Sphere {
+                center: Vec3::new(0., 0., -2.),
+                radius: 1.,
+                material,
+            }));
+            let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+            // The same seed makes both paths bounce identically.
+            reseed(7);
+            ray_color(&ray, &world, &Background::sky(), 8)
+        };
+
+        let plain = color(gray);
+        let lit = color(glowing);
+        assert!(plain.max_component() > 0.);
+        assert!(
+            lit.approx_eq(plain + emit, 1e-6),
+            "{:?} vs {:?}",
+            lit,
+            plain
+        );
+    }
+
+    #[test]
+    fn progress_counts_down_the_scanlines() {
+        let progress = Progress::new(6, "Scanlines", true);
+        let remaining: Vec<usize> = (0..6).map(|_| progress.tick()).collect();
+        assert_eq!(remaining, vec![5, 4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn stratified_sampling_has_lower_variance_than_random() {
+        let (camera, world) = disc_scene();
+        // With coordinates normalized by (size - 1), a pixel of a 2x2 image
+        // spans the whole view: a white disc on black, so the only noise
+        // comes from where samples land.
+        let variance = |sampling| {
+            let config = RenderConfig {
+                image_width: 2,
+                image_height: 2,
+                samples_per_pixel: 16,
+                background: Background::SolidColor(Vec3::new(0., 0., 0.)),
+                sampling,
+                ..config(&camera, &world)
+            };
+            let values: Vec<f32> = (0..400)
+                .map(|seed| render_pixel(&config, 0, 0, seed).unwrap().x)
+                .collect();
+            let mean = values.iter().sum::<f32>() / values.len() as f32;
+            values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32
+        };
+        let (random, stratified) = (variance(Sampling::Random), variance(Sampling::Stratified));
+        assert!(stratified < random / 2., "{} vs {}", stratified, random);
+    }
+
+    #[test]
+    fn nan_attenuation_leaves_pixels_finite() {
+        use crate::material::Material;
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        // Scatters straight back with a NaN attenuation.
+        #[derive(Debug)]
+        struct Poison;
+        impl Material for Poison {
+            fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+                let scattered = Ray::new(hit.p, -ray.dir);
+                Some((scattered, Vec3::new(f32::NAN, 0.5, 0.5)))
+            }
+        }
+
+        let (camera, _) = disc_scene();
+        let mut world = HittableList::default();
+        world.add(Box::new(Sphere {
+            center: Vec3::new(0., 0., -2.),
+            radius: 1.,
+            material: Arc::new(Poison),
+        }));
+        let config = config(&camera, &world);
+        // The center pixel sees the sphere; unguarded, its path turns NaN.
+        let ray = camera.get_ray(0.5, 0.5);
+        assert!(ray_color(&ray, &world, &Background::sky(), 8).x.is_nan());
+        let (i, j) = (config.image_width / 2, config.image_height / 2);
+        let pixel = render_pixel(&config, i, j, 51).unwrap();
+        assert!(pixel.x.is_finite() && pixel.y.is_finite() && pixel.z.is_finite());
+    }
+
+    #[test]
+    fn depth_is_finite_only_on_the_silhouette() {
+        let (camera, world) = disc_scene();
+        let (width, height) = (9, 9);
+        let depth = render_depth(&camera, &world, width, height);
+        assert_eq!(depth.len(), 81);
+        // The center looks straight at the near pole, 1 unit away.
+        assert!((depth[40] - 1.).abs() < 1e-5, "{}", depth[40]);
+        // The corners look past the sphere.
+        for &corner in &[0, 8, 72, 80] {
+            assert_eq!(depth[corner], f32::INFINITY);
+        }
+        let hits = depth.iter().filter(|t| t.is_finite()).count();
+        assert!(hits > 1 && hits < 81, "{}", hits);
+    }
+
+    #[test]
+    fn normal_color_at_the_closest_point() {
+        let (_, world) = disc_scene();
+        // The closest point faces the camera, normal (0, 0, 1).
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        assert_eq!(
+            normal_color(&ray, &world, &Background::sky()),
+            Vec3::new(0.5, 0.5, 1.)
+        );
+    }
+
+    #[test]
+    fn roulette_matches_fixed_depth_on_average() {
+        let (camera, world) = builtin_scene(8. / 6.);
+        let mean = |termination| {
+            let config = RenderConfig {
+                samples_per_pixel: 64,
+                termination,
+                ..config(&camera, &world)
+            };
+            let image = render_with_seed(56, config).unwrap();
+            let sum = image.iter().fold(Vec3::new(0., 0., 0.), |acc, &p| acc + p);
+            luminance(sum) / (image.len() * 64) as f32
+        };
+        let fixed = mean(Termination::FixedDepth);
+        let roulette = mean(Termination::RussianRoulette);
+        assert!(
+            (roulette - fixed).abs() < 0.02 * fixed,
+            "{} vs {}",
+            roulette,
+            fixed
+        );
+    }
+
+    #[test]
+    fn square_render_of_a_centered_sphere_is_symmetric() {
+        let (camera, world) = disc_scene();
+        let n = 100;
+        let depth = render_depth(&camera, &world, n, n);
+        let covered = |i: i32, j: i32| depth[(j * n + i) as usize].is_finite();
+        for j in 0..n {
+            for i in 0..n {
+                assert_eq!(covered(i, j), covered(n - 1 - i, j), "({}, {})", i, j);
+                assert_eq!(covered(i, j), covered(i, n - 1 - j), "({}, {})", i, j);
+            }
+        }
+        // Round, not stretched: as wide as it is tall.
+        let width = (0..n).filter(|&i| covered(i, n / 2)).count();
+        let height = (0..n).filter(|&j| covered(n / 2, j)).count();
+        assert_eq!(width, height);
+    }
+
+    #[test]
+    fn occlusion_darkens_where_a_sphere_meets_the_ground() {
+        use crate::material::Lambertian;
+        use crate::plane::Plane;
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        reseed(60);
+        let gray = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+        let mut world = HittableList::default();
+        world.add(Box::new(Plane {
+            point: Vec3::new(0., 0., 0.),
+            normal: Vec3::new(0., 1., 0.),
+            material: gray.clone(),
+        }));
+        world.add(Box::new(Sphere {
+            center: Vec3::new(0., 1., 0.),
+            radius: 1.,
+            material: gray.clone(),
+        }));
+
+        let up = Vec3::new(0., 1., 0.);
+        let ground = |x: f32| {
+            let hit = Hit::new(1., Vec3::new(x, 0., 0.), up, true, gray.as_ref());
+            occlusion(&world, &hit, 0., 512, 2.)
+        };
+        let (contact, open) = (ground(0.3), ground(10.));
+        assert_eq!(open, 1.);
+        assert!(contact < 0.8, "{}", contact);
+    }
+
+    #[test]
+    fn sampling_a_small_light_reduces_noise() {
+        use crate::material::{DiffuseLight, Lambertian};
+        use crate::plane::Plane;
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        let light = Sphere {
+            center: Vec3::new(0., 3., 0.),
+            radius: 0.2,
+            material: Arc::new(DiffuseLight {
+                emit: Vec3::new(50., 50., 50.),
+            }),
+        };
+        let mut world = HittableList::default();
+        world.add(Box::new(Plane {
+            point: Vec3::new(0., 0., 0.),
+            normal: Vec3::new(0., 1., 0.),
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }));
+        world.add(Box::new(light.clone()));
+        let mut lights = HittableList::default();
+        lights.add(Box::new(light));
+
+        // Looking down at the floor under the light.
+        let camera = Camera::new(
+            Vec3::new(0., 1., 3.),
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            30.,
+            1.,
+            0.,
+            1.,
+        );
+        let stats = |lights: Option<&dyn Hittable>| {
+            let config = RenderConfig {
+                image_width: 3,
+                image_height: 3,
+                samples_per_pixel: 16,
+                background: Background::SolidColor(Vec3::new(0., 0., 0.)),
+                lights,
+                ..config(&camera, &world)
+            };
+            let values: Vec<f32> = (0..200)
+                .map(|seed| render_pixel(&config, 1, 1, seed).unwrap().x)
+                .collect();
+            let mean = values.iter().sum::<f32>() / values.len() as f32;
+            let variance =
+                values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32;
+            (mean, variance)
+        };
+        let (sampled_mean, sampled) = stats(Some(&lights));
+        let (blind_mean, blind) = stats(None);
+        assert!(sampled < blind / 4., "{} vs {}", sampled, blind);
+        // Both estimate the same light, within three standard errors.
+        let tolerance = 3. * (blind / 200.).sqrt();
+        assert!(sampled_mean > 0.);
+        assert!((sampled_mean - blind_mean).abs() < tolerance);
+    }
+
+    #[test]
+    fn firefly_clamp_limits_only_bright_samples() {
+        use crate::material::DiffuseLight;
+        use crate::sphere::Sphere;
+        use std::sync::Arc;
+
+        let (camera, _) = disc_scene();
+        let pixel = |emit: f32, firefly_clamp| {
+            let mut world = HittableList::default();
+            world.add(Box::new(Sphere {
+                center: Vec3::new(0., 0., -2.),
+                radius: 1.,
+                material: Arc::new(DiffuseLight {
+                    emit: Vec3::new(emit, emit, emit),
+                }),
+            }));
+            let config = RenderConfig {
+                image_width: 9,
+                image_height: 9,
+                firefly_clamp,
+                ..config(&camera, &world)
+            };
+            // Pixel (4, 4) lies wholly inside the light's silhouette.
+            render_pixel(&config, 4, 4, 83).unwrap()
+        };
+        assert_eq!(pixel(1000., Some(10.)), Vec3::new(10., 10., 10.));
+        assert_eq!(pixel(0.5, Some(10.)), pixel(0.5, None));
+        assert_eq!(pixel(0.5, None), Vec3::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn min_bounces_outlast_certain_roulette() {
+        use crate::material::Material;
+        use crate::plane::Plane;
+        use std::sync::{Arc, Mutex};
+
+        // Black mirror: reflects straight back with zero attenuation, so
+        // roulette would end every path after its first bounce. Counts the
+        // bounces it sees.
+        #[derive(Debug, Default)]
+        struct Counter {
+            bounces: Mutex<i32>,
+        }
+        impl Material for Counter {
+            fn scatter(&self, ray: &Ray, hit: &Hit) -> Option<(Ray, Vec3)> {
+                *self.bounces.lock().unwrap() += 1;
+                let back = ray.dir.reflect(hit.normal);
+                Some((Ray::new(hit.p, back), Vec3::new(0., 0., 0.)))
+            }
+        }
+
+        // Two facing planes, so every reflection hits the other one.
+        let counter = Arc::new(Counter::default());
+        let mut world = HittableList::default();
+        for &y in &[-1., 1.] {
+            world.add(Box::new(Plane {
+                point: Vec3::new(0., y, 0.),
+                normal: Vec3::new(0., -y, 0.),
+                material: counter.clone(),
+            }));
+        }
+        let path = PathOptions {
+            max_depth: 2,
+            min_bounces: 5,
+            termination: Termination::RussianRoulette,
+            normalization: Normalization::Exact,
+        };
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0.3, 1., 0.));
+        trace(&ray, &world, &Background::sky(), None, path);
+        assert_eq!(*counter.bounces.lock().unwrap(), 5);
+    }
+
+    #[test]
+    fn center_pixel_sees_the_sphere_not_the_sky() {
+        let (camera, world) = disc_scene();
+        let config = RenderConfig {
+            image_width: 9,
+            image_height: 9,
+            ..config(&camera, &world)
+        };
+        let center = render_pixel(&config, 4, 4, 89).unwrap();
+        let sky = Background::sky().color(&camera.get_ray(0.5, 0.5));
+        assert!(!center.approx_eq(sky, 0.05), "{:?}", center);
+        assert_eq!(center, Vec3::new(1., 1., 1.));
+    }
+}
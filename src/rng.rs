@@ -0,0 +1,19 @@
+use std::cell::RefCell;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Per-thread generator behind every random draw in the renderer. Seeding it
+// per pixel makes renders reproducible regardless of thread scheduling.
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+// Uniform in [0, 1).
+pub(crate) fn random_f32() -> f32 {
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
+
+pub(crate) fn reseed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::camera::Camera;
+use crate::hittable::HittableList;
+use crate::material::{Dielectric, DiffuseLight, Glowing, Lambertian, Material, Metal};
+use crate::sphere::Sphere;
+use crate::vec3::Vec3;
+
+#[derive(Debug, Deserialize)]
+pub struct Scene {
+    pub camera: CameraSpec,
+    // Named materials that objects can reference by key.
+    #[serde(default)]
+    pub materials: HashMap<String, MaterialSpec>,
+    pub objects: Vec<ObjectSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CameraSpec {
+    pub lookfrom: [f32; 3],
+    pub lookat: [f32; 3],
+    #[serde(default = "CameraSpec::default_vup")]
+    pub vup: [f32; 3],
+    pub vfov: f32,
+    #[serde(default)]
+    pub aperture: f32,
+    #[serde(default = "CameraSpec::default_focus_dist")]
+    pub focus_dist: f32,
+}
+
+impl CameraSpec {
+    fn default_vup() -> [f32; 3] {
+        [0., 1., 0.]
+    }
+
+    fn default_focus_dist() -> f32 {
+        1.
+    }
+
+    pub fn build(&self, aspect_ratio: f32) -> Camera {
+        Camera::new(
+            Vec3::from(self.lookfrom),
+            Vec3::from(self.lookat),
+            Vec3::from(self.vup),
+            self.vfov,
+            aspect_ratio,
+            self.aperture,
+            self.focus_dist,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ObjectSpec {
+    Sphere {
+        center: [f32; 3],
+        radius: f32,
+        material: MaterialRef,
+    },
+}
+
+// Either the key of a material in the scene's materials table or an
+// inline definition.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum MaterialRef {
+    Named(String),
+    Inline(MaterialSpec),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MaterialSpec {
+    Lambertian {
+        albedo: [f32; 3],
+    },
+    Metal {
+        albedo: [f32; 3],
+        fuzz: f32,
+    },
+    Dielectric {
+        ir: f32,
+    },
+    #[serde(rename = "diffuse_light")]
+    DiffuseLight {
+        emit: [f32; 3],
+    },
+    // Any other material, also giving off emit.
+    Glowing {
+        material: Box<MaterialSpec>,
+        emit: [f32; 3],
+    },
+}
+
+impl MaterialSpec {
+    pub fn build(&self) -> Arc<dyn Material> {
+        match *self {
+            MaterialSpec::Lambertian { albedo } => Arc::new(Lambertian::new(Vec3::from(albedo))),
+            MaterialSpec::Metal { albedo, fuzz } => Arc::new(Metal::new(Vec3::from(albedo), fuzz)),
+            MaterialSpec::Dielectric { ir } => Arc::new(Dielectric { ir }),
+            MaterialSpec::DiffuseLight { emit } => Arc::new(DiffuseLight {
+                emit: Vec3::from(emit),
+            }),
+            MaterialSpec::Glowing { ref material, emit } => Arc::new(Glowing {
+                material: material.build(),
+                emit: Vec3::from(emit),
+            }),
+        }
+    }
+}
+
+// Built materials by name, so every reference to a key shares one Arc.
+#[derive(Debug, Default)]
+pub struct MaterialRegistry {
+    materials: HashMap<String, Arc<dyn Material>>,
+}
+
+impl MaterialRegistry {
+    pub fn insert(&mut self, name: String, material: Arc<dyn Material>) {
+        self.materials.insert(name, material);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Material>> {
+        self.materials.get(name).cloned()
+    }
+
+    pub fn resolve(&self, material: &MaterialRef) -> io::Result<Arc<dyn Material>> {
+        match material {
+            MaterialRef::Named(name) => self.get(name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown material '{}'", name),
+                )
+            }),
+            MaterialRef::Inline(spec) => Ok(spec.build()),
+        }
+    }
+}
+
+impl Scene {
+    pub fn registry(&self) -> MaterialRegistry {
+        let mut registry = MaterialRegistry::default();
+        for (name, spec) in &self.materials {
+            registry.insert(name.clone(), spec.build());
+        }
+        registry
+    }
+
+    // Objects with their material references resolved.
+    fn spheres(&self) -> io::Result<Vec<Sphere>> {
+        let registry = self.registry();
+        self.objects
+            .iter()
+            .map(|obj| match obj {
+                ObjectSpec::Sphere {
+                    center,
+                    radius,
+                    material,
+                } => Ok(Sphere {
+                    center: Vec3::from(*center),
+                    radius: *radius,
+                    material: registry.resolve(material)?,
+                }),
+            })
+            .collect()
+    }
+
+    // The renderable objects, plus the emissive ones again for sampling
+    // light directly. Both lists share the same material Arcs.
+    pub fn world_and_lights(&self) -> io::Result<(HittableList, HittableList)> {
+        let mut world = HittableList::default();
+        let mut lights = HittableList::default();
+        for sphere in self.spheres()? {
+            if sphere.material.emitted().max_component() > 0. {
+                lights.add(Box::new(sphere.clone()));
+            }
+            world.add(Box::new(sphere));
+        }
+        Ok((world, lights))
+    }
+
+    // Flags degenerate geometry and camera settings that would otherwise
+    // render as silently black or empty images.
+    pub fn validate(&self) -> Result<(), Vec<SceneWarning>> {
+        let mut warnings = Vec::new();
+
+        let camera = &self.camera;
+        let vectors = [
+            ("lookfrom", camera.lookfrom),
+            ("lookat", camera.lookat),
+            ("vup", camera.vup),
+        ];
+        for (field, v) in vectors {
+            if !v.iter().all(|c| c.is_finite()) {
+                warnings.push(SceneWarning::Camera { field });
+            }
+        }
+        let scalars = [
+            ("vfov", camera.vfov),
+            ("aperture", camera.aperture),
+            ("focus_dist", camera.focus_dist),
+        ];
+        for (field, x) in scalars {
+            if !x.is_finite() {
+                warnings.push(SceneWarning::Camera { field });
+            }
+        }
+
+        if self.objects.is_empty() {
+            warnings.push(SceneWarning::Empty);
+        }
+        for (index, obj) in self.objects.iter().enumerate() {
+            match obj {
+                ObjectSpec::Sphere { center, radius, .. } => {
+                    if !center.iter().all(|c| c.is_finite()) {
+                        warnings.push(SceneWarning::NonFiniteCenter { index });
+                    }
+                    // Checked first so -inf isn't passed off as a legal
+                    // negative radius.
+                    if !radius.is_finite() {
+                        warnings.push(SceneWarning::NonFiniteRadius { index });
+                    } else if *radius == 0. {
+                        warnings.push(SceneWarning::ZeroRadius { index });
+                    } else if *radius < 0. {
+                        warnings.push(SceneWarning::NegativeRadius { index });
+                    }
+                }
+            }
+        }
+
+        match warnings.is_empty() {
+            true => Ok(()),
+            false => Err(warnings),
+        }
+    }
+}
+
+// A problem found by Scene::validate; index is the position in objects.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SceneWarning {
+    ZeroRadius { index: usize },
+    // Legal (an inside-out shell, e.g. the inner wall of hollow glass) but
+    // often a typo.
+    NegativeRadius { index: usize },
+    NonFiniteRadius { index: usize },
+    NonFiniteCenter { index: usize },
+    Camera { field: &'static str },
+    Empty,
+}
+
+impl SceneWarning {
+    // Whether the scene can't render sensibly; negative radii can.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, SceneWarning::NegativeRadius { .. })
+    }
+}
+
+impl fmt::Display for SceneWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneWarning::ZeroRadius { index } => write!(f, "object {} has zero radius", index),
+            SceneWarning::NegativeRadius { index } => write!(
+                f,
+                "object {} has a negative radius and will render inside-out",
+                index
+            ),
+            SceneWarning::NonFiniteRadius { index } => {
+                write!(f, "object {} has a non-finite radius", index)
+            }
+            SceneWarning::NonFiniteCenter { index } => {
+                write!(f, "object {} has a non-finite center", index)
+            }
+            SceneWarning::Camera { field } => write!(f, "camera {} is not finite", field),
+            SceneWarning::Empty => write!(f, "scene has no objects"),
+        }
+    }
+}
+
+// Parses a JSON scene, rejecting references to undefined materials.
+pub fn load_scene(path: &str) -> io::Result<Scene> {
+    let reader = BufReader::new(File::open(path)?);
+    let scene: Scene = serde_json::from_reader(reader)?;
+    scene.spheres()?;
+    Ok(scene)
+}
+
+// The three-sphere scene used when no --scene is given.
+pub fn builtin_scene(aspect_ratio: f32) -> (Camera, HittableList) {
+    // Camera
+    let cam = Camera::new(
+        Vec3::new(0., 0., 0.),
+        Vec3::new(0., 0., -1.),
+        Vec3::new(0., 1., 0.),
+        90.,
+        aspect_ratio,
+        0.,
+        1.,
+    );
+
+    //
+    // Objects in scene.
+    //
+    let diffuse: Arc<dyn Material> = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+
+    let s1 = Sphere {
+        center: Vec3 {
+            x: 9.,
+            y: 0.,
+            z: -10.,
+        },
+        radius: 4.,
+        material: diffuse.clone(),
+    };
+
+    let s2 = Sphere {
+        center: Vec3 {
+            x: 0.,
+            y: 0.,
+            z: -10.,
+        },
+        radius: 4.,
+        material: diffuse.clone(),
+    };
+
+    let s3 = Sphere {
+        center: Vec3 {
+            x: -9.,
+            y: 0.0,
+            z: -10.,
+        },
+        radius: 4.,
+        material: diffuse.clone(),
+    };
+
+    let mut world = HittableList::default();
+    world.add(Box::new(s1));
+    world.add(Box::new(s2));
+    world.add(Box::new(s3));
+
+    (cam, world)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(json: &str) -> Scene {
+        serde_json::from_str(json).unwrap()
+    }
+
+    const SHARED: &str = r#"{
+        "camera": { "lookfrom": [0, 0, 0], "lookat": [0, 0, -1], "vfov": 90 },
+        "materials": {
+            "lamp": { "type": "diffuse_light", "emit": [4, 4, 4] },
+            "gray": { "type": "lambertian", "albedo": [0.5, 0.5, 0.5] }
+        },
+        "objects": [
+            { "type": "sphere", "center": [0, 0, -1], "radius": 0.5, "material": "gray" },
+            { "type": "sphere", "center": [1, 0, -1], "radius": 0.5, "material": "gray" },
+            { "type": "sphere", "center": [0, 2, -1], "radius": 0.5, "material": "lamp" }
+        ]
+    }"#;
+
+    #[test]
+    fn spheres_share_named_materials() {
+        let spheres = scene(SHARED).spheres().unwrap();
+        assert!(Arc::ptr_eq(&spheres[0].material, &spheres[1].material));
+        assert!(!Arc::ptr_eq(&spheres[0].material, &spheres[2].material));
+    }
+
+    #[test]
+    fn lights_share_materials_with_the_world() {
+        use crate::hittable::{Hittable, Intersection};
+        use crate::ray::Ray;
+
+        let (world, lights) = scene(SHARED).world_and_lights().unwrap();
+        assert_eq!((world.objects.len(), lights.objects.len()), (3, 1));
+
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 2., -1.));
+        let material = |list: &HittableList| match list.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => h.material as *const dyn Material as *const u8,
+            Intersection::Missed => panic!("ray should hit the lamp"),
+        };
+        assert_eq!(material(&world), material(&lights));
+    }
+
+    fn spheres(objects: &str) -> Scene {
+        scene(&format!(
+            r#"{{
+                "camera": {{ "lookfrom": [0, 0, 0], "lookat": [0, 0, -1], "vfov": 90 }},
+                "objects": [{}]
+            }}"#,
+            objects
+        ))
+    }
+
+    fn sphere(radius: f32) -> String {
+        format!(
+            r#"{{ "type": "sphere", "center": [0, 0, -2], "radius": {},
+                  "material": {{ "type": "dielectric", "ir": 1.5 }} }}"#,
+            radius
+        )
+    }
+
+    #[test]
+    fn valid_scene_has_no_warnings() {
+        assert_eq!(spheres(&sphere(1.)).validate(), Ok(()));
+        assert_eq!(scene(SHARED).validate(), Ok(()));
+    }
+
+    #[test]
+    fn zero_radius_is_an_error() {
+        let warnings = spheres(&sphere(0.)).validate().unwrap_err();
+        assert_eq!(warnings, vec![SceneWarning::ZeroRadius { index: 0 }]);
+        assert!(warnings[0].is_error());
+    }
+
+    #[test]
+    fn negative_infinite_radius_is_non_finite() {
+        // -1e39 overflows f32 to -inf when parsed.
+        let json = sphere(-1.).replace("-1", "-1e39");
+        let warnings = spheres(&json).validate().unwrap_err();
+        assert_eq!(warnings, vec![SceneWarning::NonFiniteRadius { index: 0 }]);
+        assert!(warnings[0].is_error());
+    }
+
+    #[test]
+    fn empty_scene_is_an_error() {
+        let warnings = spheres("").validate().unwrap_err();
+        assert_eq!(warnings, vec![SceneWarning::Empty]);
+        assert!(warnings[0].is_error());
+    }
+
+    #[test]
+    fn negative_radius_warns_and_renders_inside_out() {
+        use crate::bvh::bvh_or_list;
+        use crate::hittable::Intersection;
+        use crate::ray::Ray;
+
+        let scene = spheres(&sphere(-1.));
+        let warnings = scene.validate().unwrap_err();
+        assert_eq!(warnings, vec![SceneWarning::NegativeRadius { index: 0 }]);
+        assert!(!warnings[0].is_error());
+
+        // Seen from outside through the BVH, the surface is hit but reports
+        // its back face, as the inner wall of a hollow shell would.
+        let world = bvh_or_list(scene.world_and_lights().unwrap().0);
+        let ray = Ray::new(Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        match world.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                assert!((h.t - 1.).abs() < 1e-5);
+                assert!(!h.front);
+                assert_eq!(h.normal, Vec3::new(0., 0., 1.));
+            }
+            Intersection::Missed => panic!("negative-radius sphere was not hit"),
+        }
+    }
+
+    #[test]
+    fn glowing_materials_scatter_and_count_as_lights() {
+        let json = r#"{
+            "camera": { "lookfrom": [0, 0, 0], "lookat": [0, 0, -1], "vfov": 90 },
+            "objects": [
+                { "type": "sphere", "center": [0, 0, -1], "radius": 0.5, "material": {
+                    "type": "glowing",
+                    "material": { "type": "lambertian", "albedo": [0.5, 0.5, 0.5] },
+                    "emit": [1, 2, 3]
+                } }
+            ]
+        }"#;
+        let spheres = scene(json).spheres().unwrap();
+        assert_eq!(spheres[0].material.emitted(), Vec3::new(1., 2., 3.));
+        let (_, lights) = scene(json).world_and_lights().unwrap();
+        assert_eq!(lights.objects.len(), 1);
+    }
+}
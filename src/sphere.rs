@@ -0,0 +1,353 @@
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+#[cfg(feature = "serde")]
+use crate::material::Lambertian;
+use crate::material::Material;
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::rng::random_f32;
+use crate::vec3::{random_unit_vector, Vec3};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+    // Materials are trait objects and aren't serialized; spheres reload
+    // with the default gray Lambertian.
+    #[cfg_attr(feature = "serde", serde(skip, default = "Sphere::default_material"))]
+    pub material: Arc<dyn Material>,
+}
+
+impl Sphere {
+    // True for points inside or on the surface.
+    pub fn contains(&self, p: Vec3) -> bool {
+        p.distance_squared(self.center) <= self.radius * self.radius
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        4. * std::f32::consts::PI * self.radius * self.radius
+    }
+
+    // Intersection with a sphere at an explicit center, shared with MovingSphere.
+    pub(crate) fn hit_at<'a>(
+        center: Vec3,
+        radius: f32,
+        material: &'a dyn Material,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+    ) -> Intersection<'a> {
+        let oc = ray.origin - center;
+        let a = ray.dir.dot(ray.dir);
+        let half_b = oc.dot(ray.dir);
+        let c = oc.dot(oc) - radius * radius;
+        let discriminant = half_b * half_b - a * c;
+
+        if discriminant < 0. {
+            return Intersection::Missed;
+        }
+
+        let sqrtd = discriminant.sqrt();
+
+        // Find the nearest root that lies in the acceptable range.
+        let mut root = (-half_b - sqrtd) / a;
+        if !(t_min..=t_max).contains(&root) {
+            root = (-half_b + sqrtd) / a;
+            if !(t_min..=t_max).contains(&root) {
+                return Intersection::Missed;
+            }
+        }
+
+        let p = ray.at(root);
+        let outward_normal: Vec3 = (p - center) / radius;
+        let (u, v) = Sphere::uv(outward_normal);
+
+        Intersection::Hit(Hit::facing(root, p, ray, outward_normal, material).with_uv(u, v))
+    }
+
+    // Spherical mapping of a point on the unit sphere to [0,1] x [0,1].
+    fn uv(p: Vec3) -> (f32, f32) {
+        let theta = (-p.y).acos();
+        let phi = (-p.z).atan2(p.x) + std::f32::consts::PI;
+        (
+            phi / (2. * std::f32::consts::PI),
+            theta / std::f32::consts::PI,
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Sphere {
+    fn default_material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))
+    }
+}
+
+impl Hittable for Sphere {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        Sphere::hit_at(
+            self.center,
+            self.radius,
+            self.material.as_ref(),
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    // Negative radii (inside-out shells) cover the same space as positive ones.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius).abs();
+        Some(Aabb {
+            min: self.center - r,
+            max: self.center + r,
+        })
+    }
+
+    // Uniform over the cone of directions from origin that see the sphere.
+    fn pdf_value(&self, origin: Vec3, dir: Vec3) -> f32 {
+        let ray = Ray::new(origin, dir);
+        let dist2 = origin.distance_squared(self.center);
+        let r2 = self.radius * self.radius;
+        match self.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(_) if dist2 > r2 => {
+                let cos_theta_max = (1. - r2 / dist2).sqrt();
+                1. / (2. * std::f32::consts::PI * (1. - cos_theta_max))
+            }
+            _ => 0.,
+        }
+    }
+
+    fn random(&self, origin: Vec3) -> Vec3 {
+        let dir = self.center - origin;
+        let dist2 = dir.len2();
+        let r2 = self.radius * self.radius;
+        // Points inside the sphere see it in every direction.
+        if dist2 <= r2 {
+            return random_unit_vector();
+        }
+
+        let cos_theta_max = (1. - r2 / dist2).sqrt();
+        let r1 = random_f32();
+        let z = 1. + random_f32() * (cos_theta_max - 1.);
+        let phi = 2. * std::f32::consts::PI * r1;
+        let sin = (1. - z * z).sqrt();
+        Onb::from_w(dir).local(Vec3::new(phi.cos() * sin, phi.sin() * sin, z))
+    }
+}
+
+// Sphere whose center moves linearly from center0 at time0 to center1 at time1.
+#[derive(Debug, Clone)]
+pub struct MovingSphere {
+    pub center0: Vec3,
+    pub center1: Vec3,
+    pub time0: f32,
+    pub time1: f32,
+    pub radius: f32,
+    pub material: Arc<dyn Material>,
+}
+
+impl MovingSphere {
+    fn center(&self, time: f32) -> Vec3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        self.center0.lerp(
+            self.center1,
+            (time - self.time0) / (self.time1 - self.time0),
+        )
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        Sphere::hit_at(
+            self.center(ray.time),
+            self.radius,
+            self.material.as_ref(),
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    // Encloses the sphere over the whole shutter interval.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius).abs();
+        let box0 = Aabb {
+            min: self.center(self.time0) - r,
+            max: self.center(self.time0) + r,
+        };
+        let box1 = Aabb {
+            min: self.center(self.time1) - r,
+            max: self.center(self.time1) + r,
+        };
+        Some(Aabb::surrounding(box0, box1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn sphere(radius: f32) -> Sphere {
+        Sphere {
+            center: Vec3::new(0., 0., 0.),
+            radius,
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    #[test]
+    fn unit_sphere_bounding_box() {
+        let b = sphere(1.).bounding_box().unwrap();
+        assert_eq!(b.min, Vec3::new(-1., -1., -1.));
+        assert_eq!(b.max, Vec3::new(1., 1., 1.));
+    }
+
+    #[test]
+    fn negative_radius_bounding_box_is_not_inverted() {
+        let b = sphere(-1.).bounding_box().unwrap();
+        assert_eq!(b.min, Vec3::new(-1., -1., -1.));
+        assert_eq!(b.max, Vec3::new(1., 1., 1.));
+
+        let moving = MovingSphere {
+            center0: Vec3::new(0., 0., 0.),
+            center1: Vec3::new(1., 0., 0.),
+            time0: 0.,
+            time1: 1.,
+            radius: -1.,
+            material: sphere(1.).material,
+        };
+        let b = moving.bounding_box().unwrap();
+        assert_eq!(b.min, Vec3::new(-1., -1., -1.));
+        assert_eq!(b.max, Vec3::new(2., 1., 1.));
+    }
+
+    #[test]
+    fn pdf_value_integrates_to_one_over_sampled_directions() {
+        use crate::rng::reseed;
+        use crate::vec3::random_unit_vector;
+
+        reseed(11);
+        let mut light = sphere(1.);
+        light.center = Vec3::new(0., 0., -2.);
+        let origin = Vec3::new(0., 0., 0.);
+
+        // Every direction random draws must hit the sphere.
+        for _ in 0..1000 {
+            assert!(light.pdf_value(origin, light.random(origin)) > 0.);
+        }
+
+        // Uniform directions over the sphere of directions estimate the
+        // integral of the pdf as its mean times 4 pi.
+        let n = 200_000;
+        let sum: f32 = (0..n)
+            .map(|_| light.pdf_value(origin, random_unit_vector()))
+            .sum();
+        let integral = sum / n as f32 * 4. * std::f32::consts::PI;
+        assert!((integral - 1.).abs() < 0.03, "{}", integral);
+    }
+
+    #[test]
+    fn scattered_rays_do_not_re_hit_their_own_surface() {
+        use crate::rng::reseed;
+
+        reseed(5);
+        let s = sphere(1.);
+        for k in 0..1000 {
+            // Primary rays from all around, aimed at the center.
+            let t = k as f32 * 0.61;
+            let origin = Vec3::new(t.sin() * 3., (t * 1.7).cos() * 3., t.cos() * 3.);
+            let ray = Ray::new(origin, -origin);
+            let hit = match s.hit(&ray, 0.001, f32::INFINITY) {
+                Intersection::Hit(h) => h,
+                Intersection::Missed => panic!("primary ray missed"),
+            };
+            let (scattered, _) = s.material.scatter(&ray, &hit).unwrap();
+            assert_eq!(scattered.origin, hit.p);
+            // Leaving a convex surface, the only root left would be the
+            // spurious one at t ~ 0, which t_min rejects.
+            assert!(matches!(
+                s.hit(&scattered, 0.001, f32::INFINITY),
+                Intersection::Missed
+            ));
+        }
+    }
+
+    #[test]
+    fn hits_outside_the_interval_are_missed() {
+        let s = sphere(1.);
+        // Enters at t = 4 and leaves at t = 6.
+        let ray = Ray::new(Vec3::new(0., 0., 5.), Vec3::new(0., 0., -1.));
+        assert!(matches!(s.hit(&ray, 0.001, 3.), Intersection::Missed));
+        assert!(matches!(
+            s.hit(&ray, 7., f32::INFINITY),
+            Intersection::Missed
+        ));
+        match s.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => assert_eq!(h.t, 4.),
+            Intersection::Missed => panic!("ray through the center missed"),
+        }
+    }
+
+    #[test]
+    fn uv_at_the_poles_and_the_front() {
+        let close = |(u, v): (f32, f32), (eu, ev): (f32, f32)| {
+            assert!(
+                (u - eu).abs() < 1e-6 && (v - ev).abs() < 1e-6,
+                "{:?}",
+                (u, v)
+            );
+        };
+        // u = 0.5 on +x, the seam of the mapping runs along -x.
+        close(Sphere::uv(Vec3::new(1., 0., 0.)), (0.5, 0.5));
+        close(Sphere::uv(Vec3::new(0., 0., 1.)), (0.25, 0.5));
+        assert!((Sphere::uv(Vec3::new(0., 1., 0.)).1 - 1.).abs() < 1e-6);
+        assert!(Sphere::uv(Vec3::new(0., -1., 0.)).1.abs() < 1e-6);
+    }
+
+    #[test]
+    fn moving_sphere_follows_its_path() {
+        let moving = MovingSphere {
+            center0: Vec3::new(0., 0., -3.),
+            center1: Vec3::new(4., 0., -3.),
+            time0: 0.,
+            time1: 1.,
+            radius: 0.5,
+            material: sphere(1.).material,
+        };
+        assert_eq!(moving.center(0.), moving.center0);
+        assert_eq!(moving.center(1.), moving.center1);
+
+        // Halfway through, the center is at x = 2.
+        let ray = Ray::new_at_time(Vec3::new(2., 0., 0.), Vec3::new(0., 0., -1.), 0.5);
+        match moving.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => assert_eq!(h.p, Vec3::new(2., 0., -2.5)),
+            Intersection::Missed => panic!("ray missed the sphere at its midpoint"),
+        }
+        let early = Ray::new_at_time(ray.origin, ray.dir, 0.);
+        assert!(matches!(
+            moving.hit(&early, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+
+    #[test]
+    fn contains_and_surface_area() {
+        let mut s = sphere(2.);
+        s.center = Vec3::new(1., 1., 1.);
+        assert!(s.contains(s.center));
+        assert!(s.contains(Vec3::new(3., 1., 1.)));
+        assert!(!s.contains(Vec3::new(10., 1., 1.)));
+        let unit = sphere(1.).surface_area();
+        assert!((unit - 4. * std::f32::consts::PI).abs() < 1e-6, "{}", unit);
+    }
+}
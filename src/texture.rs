@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::vec3::{clip, Vec3};
+
+pub trait Texture: fmt::Debug + Send + Sync {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3;
+}
+
+#[derive(Debug)]
+pub struct SolidColor(pub Vec3);
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f32, _v: f32, _p: Vec3) -> Vec3 {
+        self.0
+    }
+}
+
+// 3D checker pattern; scale is the spatial frequency of the cells.
+#[derive(Debug)]
+pub struct CheckerTexture {
+    pub odd: Box<dyn Texture>,
+    pub even: Box<dyn Texture>,
+    pub scale: f32,
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
+        let sines = (self.scale * p.x).sin() * (self.scale * p.y).sin() * (self.scale * p.z).sin();
+        match sines < 0. {
+            true => self.odd.value(u, v, p),
+            false => self.even.value(u, v, p),
+        }
+    }
+}
+
+// Shown in place of an empty image; cyan makes it obvious in the render.
+pub(crate) const EMPTY_IMAGE_COLOR: Vec3 = Vec3 {
+    x: 0.,
+    y: 1.,
+    z: 1.,
+};
+
+// The texel nearest (u, v), where u runs left to right and v top to bottom,
+// both clipped to [0,1]. None for an empty image.
+pub(crate) fn nearest_texel<P: image::Pixel>(
+    image: &image::ImageBuffer<P, Vec<P::Subpixel>>,
+    u: f32,
+    v: f32,
+) -> Option<&P> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let i = ((clip(u, 0., 1.) * width as f32) as u32).min(width - 1);
+    let j = ((clip(v, 0., 1.) * height as f32) as u32).min(height - 1);
+    Some(image.get_pixel(i, j))
+}
+
+// Nearest-texel lookup into a loaded image.
+#[derive(Debug)]
+pub struct ImageTexture {
+    image: image::RgbImage,
+}
+
+impl ImageTexture {
+    pub fn new(path: &str) -> image::ImageResult<ImageTexture> {
+        Ok(ImageTexture {
+            image: image::open(path)?.to_rgb8(),
+        })
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f32, v: f32, _p: Vec3) -> Vec3 {
+        // Image rows go top-down, so v is flipped.
+        match nearest_texel(&self.image, u, 1. - clip(v, 0., 1.)) {
+            Some(texel) => {
+                let [r, g, b] = texel.0;
+                Vec3::new(r as f32, g as f32, b as f32) / 255.
+            }
+            None => EMPTY_IMAGE_COLOR,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checker_alternates_one_cell_apart() {
+        let (odd, even) = (Vec3::new(1., 0., 0.), Vec3::new(0., 0., 1.));
+        // Cells are pi / scale = 1 unit wide.
+        let checker = CheckerTexture {
+            odd: Box::new(SolidColor(odd)),
+            even: Box::new(SolidColor(even)),
+            scale: std::f32::consts::PI,
+        };
+        let p = Vec3::new(0.5, 0.5, 0.5);
+        assert_eq!(checker.value(0., 0., p), even);
+        assert_eq!(checker.value(0., 0., p + Vec3::new(1., 0., 0.)), odd);
+        assert_eq!(checker.value(0., 0., p + Vec3::new(1., 1., 0.)), even);
+    }
+
+    #[test]
+    fn image_texture_corners_of_a_2x2_image() {
+        let colors = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        let image = image::RgbImage::from_fn(2, 2, |i, j| image::Rgb(colors[(j * 2 + i) as usize]));
+        let file = format!("rsay-tracing-{}-corners.png", std::process::id());
+        let path = std::env::temp_dir().join(file);
+        image.save(&path).unwrap();
+        let texture = ImageTexture::new(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let texture = texture.unwrap();
+        let at = |u, v| texture.value(u, v, Vec3::new(0., 0., 0.));
+        // v = 1 is the top row of the image.
+        assert_eq!(at(0., 1.), Vec3::new(1., 0., 0.));
+        assert_eq!(at(1., 1.), Vec3::new(0., 1., 0.));
+        assert_eq!(at(0., 0.), Vec3::new(0., 0., 1.));
+        assert_eq!(at(1., 0.), Vec3::new(1., 1., 1.));
+        // Out of range clamps to the nearest edge.
+        assert_eq!(at(-3., 7.), Vec3::new(1., 0., 0.));
+    }
+}
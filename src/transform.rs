@@ -0,0 +1,174 @@
+use crate::aabb::Aabb;
+use crate::hittable::{Hittable, Intersection};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+// Moves a child object by offset.
+pub struct Translate {
+    pub object: Box<dyn Hittable>,
+    pub offset: Vec3,
+}
+
+impl Hittable for Translate {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let moved = Ray::new_at_time(ray.origin - self.offset, ray.dir, ray.time);
+
+        match self.object.hit(&moved, t_min, t_max) {
+            Intersection::Hit(mut h) => {
+                h.p += self.offset;
+                Intersection::Hit(h)
+            }
+            Intersection::Missed => Intersection::Missed,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.object.bounding_box().map(|b| Aabb {
+            min: b.min + self.offset,
+            max: b.max + self.offset,
+        })
+    }
+}
+
+// Rotates a child object about the y axis.
+pub struct RotateY {
+    object: Box<dyn Hittable>,
+    sin_theta: f32,
+    cos_theta: f32,
+    bbox: Option<Aabb>,
+}
+
+impl RotateY {
+    pub fn new(object: Box<dyn Hittable>, angle_deg: f32) -> RotateY {
+        let (sin_theta, cos_theta) = angle_deg.to_radians().sin_cos();
+
+        // Box around the rotated corners of the child's box.
+        let bbox = object.bounding_box().map(|b| {
+            let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+            let mut max = -min;
+            for corner in 0..8 {
+                let pick = |bit: usize, axis: usize| match corner & bit {
+                    0 => b.min[axis],
+                    _ => b.max[axis],
+                };
+                let (x, y, z) = (pick(1, 0), pick(2, 1), pick(4, 2));
+                let rotated = Vec3::new(
+                    cos_theta * x + sin_theta * z,
+                    y,
+                    -sin_theta * x + cos_theta * z,
+                );
+                min = min.min(rotated);
+                max = max.max(rotated);
+            }
+            Aabb { min, max }
+        });
+
+        RotateY {
+            object,
+            sin_theta,
+            cos_theta,
+            bbox,
+        }
+    }
+
+    // World space to object space.
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos_theta * v.x - self.sin_theta * v.z,
+            v.y,
+            self.sin_theta * v.x + self.cos_theta * v.z,
+        )
+    }
+
+    // Object space back to world space.
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos_theta * v.x + self.sin_theta * v.z,
+            v.y,
+            -self.sin_theta * v.x + self.cos_theta * v.z,
+        )
+    }
+}
+
+impl Hittable for RotateY {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let rotated = Ray::new_at_time(
+            self.to_object(ray.origin),
+            self.to_object(ray.dir),
+            ray.time,
+        );
+
+        match self.object.hit(&rotated, t_min, t_max) {
+            Intersection::Hit(mut h) => {
+                h.p = self.to_world(h.p);
+                h.normal = self.to_world(h.normal);
+                Intersection::Hit(h)
+            }
+            Intersection::Missed => Intersection::Missed,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+    use crate::sphere::Sphere;
+    use std::sync::Arc;
+
+    fn sphere(center: Vec3, radius: f32) -> Box<dyn Hittable> {
+        Box::new(Sphere {
+            center,
+            radius,
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        })
+    }
+
+    fn hit(object: &dyn Hittable, ray: &Ray) -> (f32, Vec3, Vec3) {
+        match object.hit(ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => (h.t, h.p, h.normal),
+            Intersection::Missed => panic!("{:?} missed", ray.origin),
+        }
+    }
+
+    #[test]
+    fn translated_sphere_is_hit_where_a_shifted_ray_hits_the_original() {
+        let offset = Vec3::new(5., 0., 0.);
+        let original = sphere(Vec3::new(0., 0., 0.), 1.);
+        let moved = Translate {
+            object: sphere(Vec3::new(0., 0., 0.), 1.),
+            offset,
+        };
+        let ray = Ray::new(Vec3::new(0.3, 0.2, 5.), Vec3::new(0., 0., -1.));
+        let shifted = Ray::new(ray.origin + offset, ray.dir);
+
+        let (t, p, normal) = hit(original.as_ref(), &ray);
+        let (moved_t, moved_p, moved_normal) = hit(&moved, &shifted);
+        assert!((moved_t - t).abs() < 1e-5, "{} vs {}", moved_t, t);
+        assert!(moved_p.approx_eq(p + offset, 1e-6), "{:?}", moved_p);
+        assert!(moved_normal.approx_eq(normal, 1e-5), "{:?}", moved_normal);
+        assert!(matches!(
+            moved.hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+
+    #[test]
+    fn rotated_sphere_is_hit_at_its_new_position() {
+        // A quarter turn about y carries +x to -z.
+        let rotated = RotateY::new(sphere(Vec3::new(2., 0., 0.), 0.5), 90.);
+        let ray = Ray::new(Vec3::new(0., 0., 5.), Vec3::new(0., 0., -1.));
+        let (t, p, normal) = hit(&rotated, &ray);
+        assert!((t - 6.5).abs() < 1e-5, "{}", t);
+        assert!(p.approx_eq(Vec3::new(0., 0., -1.5), 1e-5), "{:?}", p);
+        assert!(
+            normal.approx_eq(Vec3::new(0., 0., 1.), 1e-5),
+            "{:?}",
+            normal
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, Hittable, Intersection};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    pub v0: Vec3,
+    pub v1: Vec3,
+    pub v2: Vec3,
+    pub material: Arc<dyn Material>,
+}
+
+impl Hittable for Triangle {
+    // Möller–Trumbore intersection.
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Intersection<'_> {
+        let edge1 = self.v1 - self.v0;
+        let edge2 = self.v2 - self.v0;
+        let pvec = ray.dir.cross(edge2);
+        let det = edge1.dot(pvec);
+
+        // Ray runs parallel to the triangle plane.
+        if det.abs() < 1e-6 {
+            return Intersection::Missed;
+        }
+
+        let inv_det = 1. / det;
+        let tvec = ray.origin - self.v0;
+        let u = tvec.dot(pvec) * inv_det;
+        if !(0. ..=1.).contains(&u) {
+            return Intersection::Missed;
+        }
+
+        let qvec = tvec.cross(edge1);
+        let v = ray.dir.dot(qvec) * inv_det;
+        if v < 0. || u + v > 1. {
+            return Intersection::Missed;
+        }
+
+        let t = edge2.dot(qvec) * inv_det;
+        if !(t_min..=t_max).contains(&t) {
+            return Intersection::Missed;
+        }
+
+        let p = self.v0 * (1. - u - v) + self.v1 * u + self.v2 * v;
+        let outward_normal = edge1.cross(edge2).unit();
+
+        Intersection::Hit(Hit::facing(
+            t,
+            p,
+            ray,
+            outward_normal,
+            self.material.as_ref(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Padded so axis-aligned triangles don't produce a zero-width box.
+        let pad = Vec3::new(1e-4, 1e-4, 1e-4);
+        Some(Aabb {
+            min: self.v0.min(self.v1).min(self.v2) - pad,
+            max: self.v0.max(self.v1).max(self.v2) + pad,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn triangle() -> Triangle {
+        Triangle {
+            v0: Vec3::new(-1., -1., -2.),
+            v1: Vec3::new(1., -1., -2.),
+            v2: Vec3::new(0., 1., -2.),
+            material: Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        }
+    }
+
+    #[test]
+    fn ray_through_the_centroid_hits() {
+        let tri = triangle();
+        let centroid = (tri.v0 + tri.v1 + tri.v2) / 3.;
+        let ray = Ray::new(Vec3::new(0., 0., 0.), centroid);
+        match tri.hit(&ray, 0.001, f32::INFINITY) {
+            Intersection::Hit(h) => {
+                assert!(h.p.approx_eq(centroid, 1e-6), "{:?}", h.p);
+                assert_eq!(h.normal, Vec3::new(0., 0., 1.));
+                assert!(h.front);
+            }
+            Intersection::Missed => panic!("ray through the centroid missed"),
+        }
+    }
+
+    #[test]
+    fn ray_just_outside_an_edge_misses() {
+        // Just below the v0-v1 edge at y = -1.
+        let ray = Ray::new(Vec3::new(0., -1.01, 0.), Vec3::new(0., 0., -1.));
+        assert!(matches!(
+            triangle().hit(&ray, 0.001, f32::INFINITY),
+            Intersection::Missed
+        ));
+    }
+}
@@ -0,0 +1,664 @@
+use std::ops;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::rng::random_f32;
+
+// How the scatter path normalizes directions: Exact uses unit, Fast uses
+// normalize_fast, trading the last bits of precision for speed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Normalization {
+    Exact,
+    Fast,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+
+    pub fn random(min: f32, max: f32) -> Vec3 {
+        Vec3 {
+            x: random_double(min, max),
+            y: random_double(min, max),
+            z: random_double(min, max),
+        }
+    }
+
+    pub fn len2(&self) -> f32 {
+        self.x * self.x + self.y * self.y + self.z * self.z
+    }
+
+    pub fn len(&self) -> f32 {
+        self.len2().sqrt()
+    }
+
+    pub fn distance(&self, other: Vec3) -> f32 {
+        (*self - other).len()
+    }
+
+    // Cheaper than distance when only comparing.
+    pub fn distance_squared(&self, other: Vec3) -> f32 {
+        (*self - other).len2()
+    }
+
+    // Zero-length vectors stay at zero instead of turning into NaNs.
+    pub fn unit(&self) -> Vec3 {
+        let len = self.len();
+        match len < f32::EPSILON {
+            true => Vec3::new(0., 0., 0.),
+            false => *self / len,
+        }
+    }
+
+    // True when every component is within eps of the other vector's.
+    pub fn approx_eq(&self, other: Vec3, eps: f32) -> bool {
+        (self.x - other.x).abs() <= eps
+            && (self.y - other.y).abs() <= eps
+            && (self.z - other.z).abs() <= eps
+    }
+
+    // Like unit, but one reciprocal and three multiplies instead of three
+    // divides; may differ from unit in the last bits.
+    pub fn normalize_fast(&self) -> Vec3 {
+        let len = self.len();
+        match len < f32::EPSILON {
+            true => Vec3::new(0., 0., 0.),
+            false => *self * (1. / len),
+        }
+    }
+
+    // unit or normalize_fast, as mode asks.
+    pub fn normalize(&self, mode: Normalization) -> Vec3 {
+        match mode {
+            Normalization::Exact => self.unit(),
+            Normalization::Fast => self.normalize_fast(),
+        }
+    }
+
+    pub fn near_zero(&self) -> bool {
+        let s = 1e-8;
+        self.x.abs() < s && self.y.abs() < s && self.z.abs() < s
+    }
+
+    // Mirrors self about the normal.
+    pub fn reflect(&self, normal: Vec3) -> Vec3 {
+        *self - normal * 2. * self.dot(normal)
+    }
+
+    // Bends self, a unit vector, through a surface with the given normal (Snell's law).
+    pub fn refract(&self, normal: Vec3, etai_over_etat: f32) -> Vec3 {
+        let cos_theta = (-*self).dot(normal).min(1.);
+        let r_out_perp = (*self + normal * cos_theta) * etai_over_etat;
+        let r_out_parallel = normal * -(1. - r_out_perp.len2()).abs().sqrt();
+        r_out_perp + r_out_parallel
+    }
+
+    // Linear blend from self (t = 0) to other (t = 1).
+    pub fn lerp(&self, other: Vec3, t: f32) -> Vec3 {
+        *self * (1.0 - t) + other * t
+    }
+
+    // Component of self along onto; zero if onto has no length.
+    pub fn project_onto(&self, onto: Vec3) -> Vec3 {
+        let len2 = onto.len2();
+        match len2 < f32::EPSILON {
+            true => Vec3::new(0., 0., 0.),
+            false => onto * (self.dot(onto) / len2),
+        }
+    }
+
+    // Component of self perpendicular to from.
+    pub fn reject_from(&self, from: Vec3) -> Vec3 {
+        *self - self.project_onto(from)
+    }
+
+    // Component-wise minimum.
+    pub fn min(&self, v: Vec3) -> Vec3 {
+        Vec3::new(self.x.min(v.x), self.y.min(v.y), self.z.min(v.z))
+    }
+
+    // Component-wise maximum.
+    pub fn max(&self, v: Vec3) -> Vec3 {
+        Vec3::new(self.x.max(v.x), self.y.max(v.y), self.z.max(v.z))
+    }
+
+    // Clamps every component into [min, max].
+    pub fn clamp(&self, min: f32, max: f32) -> Vec3 {
+        Vec3::new(
+            clip(self.x, min, max),
+            clip(self.y, min, max),
+            clip(self.z, min, max),
+        )
+    }
+
+    // Component-wise square root; negative components become 0 instead of NaN.
+    pub fn sqrt(&self) -> Vec3 {
+        Vec3::new(
+            self.x.max(0.).sqrt(),
+            self.y.max(0.).sqrt(),
+            self.z.max(0.).sqrt(),
+        )
+    }
+
+    // Component-wise absolute value.
+    pub fn abs(&self) -> Vec3 {
+        Vec3::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    pub fn min_component(&self) -> f32 {
+        self.x.min(self.y).min(self.z)
+    }
+
+    pub fn max_component(&self) -> f32 {
+        self.x.max(self.y).max(self.z)
+    }
+
+    pub fn dot(&self, v: Vec3) -> f32 {
+        self.x * v.x + self.y * v.y + self.z * v.z
+    }
+
+    // Right-handed cross product.
+    pub fn cross(&self, v: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * v.z - self.z * v.y,
+            y: self.z * v.x - self.x * v.z,
+            z: self.x * v.y - self.y * v.x,
+        }
+    }
+}
+
+// Serialized as a plain [x, y, z] array.
+#[cfg(feature = "serde")]
+impl Serialize for Vec3 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        <[f32; 3]>::from(*self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Vec3 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec3, D::Error> {
+        <[f32; 3]>::deserialize(deserializer).map(Vec3::from)
+    }
+}
+
+impl From<[f32; 3]> for Vec3 {
+    fn from([x, y, z]: [f32; 3]) -> Vec3 {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl From<(f32, f32, f32)> for Vec3 {
+    fn from((x, y, z): (f32, f32, f32)) -> Vec3 {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl From<Vec3> for [f32; 3] {
+    fn from(v: Vec3) -> [f32; 3] {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl ops::Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl ops::Add for Vec3 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl ops::Mul<f32> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, f: f32) -> Self {
+        Self {
+            x: self.x * f,
+            y: self.y * f,
+            z: self.z * f,
+        }
+    }
+}
+
+impl ops::Mul<Vec3> for f32 {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        v * self
+    }
+}
+
+impl ops::Mul<Vec3> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+        }
+    }
+}
+
+impl ops::Div<f32> for Vec3 {
+    type Output = Self;
+
+    fn div(self, f: f32) -> Self {
+        Self {
+            x: self.x / f,
+            y: self.y / f,
+            z: self.z / f,
+        }
+    }
+}
+
+impl ops::AddAssign for Vec3 {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+    }
+}
+
+impl ops::MulAssign<f32> for Vec3 {
+    fn mul_assign(&mut self, f: f32) {
+        self.x *= f;
+        self.y *= f;
+        self.z *= f;
+    }
+}
+
+// Component by axis number: 0 = x, 1 = y, 2 = z.
+impl ops::Index<usize> for Vec3 {
+    type Output = f32;
+
+    fn index(&self, axis: usize) -> &f32 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 axis out of range: {}", axis),
+        }
+    }
+}
+
+impl ops::IndexMut<usize> for Vec3 {
+    fn index_mut(&mut self, axis: usize) -> &mut f32 {
+        match axis {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("Vec3 axis out of range: {}", axis),
+        }
+    }
+}
+
+impl ops::Neg for Vec3 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+pub fn random_in_unit_sphere() -> Vec3 {
+    loop {
+        let p = Vec3::random(-1., 1.);
+        match p.len2() >= 1. {
+            true => continue,
+            false => return p,
+        }
+    }
+}
+
+pub fn random_in_unit_disk() -> Vec3 {
+    loop {
+        let p = Vec3::new(random_double(-1., 1.), random_double(-1., 1.), 0.);
+        match p.len2() >= 1. {
+            true => continue,
+            false => return p,
+        }
+    }
+}
+
+pub fn random_unit_vector() -> Vec3 {
+    random_unit_vector_with(Normalization::Exact)
+}
+
+pub(crate) fn random_unit_vector_with(mode: Normalization) -> Vec3 {
+    random_in_unit_sphere().normalize(mode)
+}
+
+// Unit vector around +z with density cos(theta) / pi.
+pub fn random_cosine_direction() -> Vec3 {
+    let r1 = random_f32();
+    let r2 = random_f32();
+    let phi = 2. * std::f32::consts::PI * r1;
+    let r = r2.sqrt();
+    Vec3::new(phi.cos() * r, phi.sin() * r, (1. - r2).sqrt())
+}
+
+// Unit vector flipped, if needed, into the hemisphere around normal.
+pub fn random_in_hemisphere(normal: Vec3) -> Vec3 {
+    let v = random_unit_vector();
+    match v.dot(normal) > 0. {
+        true => v,
+        false => -v,
+    }
+}
+
+// Nudges the unit vector dir by a random offset of up to roughness (clamped
+// to [0, 1]) and renormalizes: the fuzz behind glossy reflections.
+pub fn perturb(dir: Vec3, roughness: f32) -> Vec3 {
+    perturb_with(dir, roughness, Normalization::Exact)
+}
+
+pub(crate) fn perturb_with(dir: Vec3, roughness: f32, mode: Normalization) -> Vec3 {
+    (dir + random_in_unit_sphere() * clip(roughness, 0., 1.)).normalize(mode)
+}
+
+pub(crate) fn clip(v: f32, min: f32, max: f32) -> f32 {
+    match v {
+        c if c > max => max,
+        c if c < min => min,
+        c => c,
+    }
+}
+
+pub(crate) fn random_double(min: f32, max: f32) -> f32 {
+    min + (max - min) * random_f32()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cross_is_orthogonal_to_both_inputs() {
+        let a = Vec3::new(1., 2., 3.);
+        let b = Vec3::new(-4., 0.5, 2.);
+        let c = a.cross(b);
+        assert!(c.dot(a).abs() < 1e-5);
+        assert!(c.dot(b).abs() < 1e-5);
+    }
+
+    #[test]
+    fn cross_follows_right_handed_basis() {
+        let (x, y, z) = (
+            Vec3::new(1., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            Vec3::new(0., 0., 1.),
+        );
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(z), x);
+        assert_eq!(z.cross(x), y);
+        assert_eq!(y.cross(x), -z);
+    }
+
+    #[test]
+    fn component_wise_mul() {
+        let white = Vec3::new(1., 1., 1.);
+        let tint = Vec3::new(0.5, 0.7, 1.0);
+        assert_eq!(white * tint, tint);
+        let a = Vec3::new(2., -3., 0.25);
+        assert_eq!(a * tint, tint * a);
+    }
+
+    #[test]
+    fn assign_ops_match_binary_ops() {
+        let vs = [
+            Vec3::new(0., 0., 0.),
+            Vec3::new(1., 2., 3.),
+            Vec3::new(-0.5, 4., 1e3),
+        ];
+        for &a in &vs {
+            for &b in &vs {
+                let mut sum = a;
+                sum += b;
+                assert_eq!(sum, a + b);
+            }
+            let mut scaled = a;
+            scaled *= 0.25;
+            assert_eq!(scaled, a * 0.25);
+        }
+    }
+
+    #[test]
+    fn unit_of_zero_is_zero() {
+        let u = Vec3::new(0., 0., 0.).unit();
+        assert!(u.x.is_finite() && u.y.is_finite() && u.z.is_finite());
+        assert_eq!(u, Vec3::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn unit_has_length_one() {
+        assert!((Vec3::new(3., -4., 12.).unit().len() - 1.).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_as_array() {
+        let v = Vec3::new(1.5, -2., 0.25);
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, "[1.5,-2.0,0.25]");
+        assert_eq!(serde_json::from_str::<Vec3>(&json).unwrap(), v);
+    }
+
+    #[test]
+    fn normalize_fast_agrees_with_unit() {
+        for k in 0..200 {
+            let t = k as f32 * 0.37;
+            let v = Vec3::new(t.sin() * 50., (t * 1.3).cos() * 0.02, t - 30.);
+            let (fast, exact) = (v.normalize_fast(), v.unit());
+            assert!(fast.approx_eq(exact, 1e-5), "{:?} vs {:?}", fast, exact);
+            assert!((fast.len() - 1.).abs() < 1e-5);
+            assert!((exact.len() - 1.).abs() < 1e-5);
+        }
+        assert_eq!(
+            Vec3::new(0., 0., 0.).normalize_fast(),
+            Vec3::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn normalize_follows_the_mode() {
+        let v = Vec3::new(1., 7., -3.);
+        assert_eq!(v.normalize(Normalization::Fast), v.normalize_fast());
+        assert_eq!(v.normalize(Normalization::Exact), v.unit());
+    }
+
+    #[test]
+    fn random_unit_vectors_have_length_one() {
+        crate::rng::reseed(15);
+        let n = 10_000;
+        let mut mean = Vec3::new(0., 0., 0.);
+        for _ in 0..n {
+            let v = random_unit_vector();
+            assert!((v.len() - 1.).abs() < 1e-5, "{:?}", v);
+            mean += v / n as f32;
+        }
+        // Spread over the whole sphere, so they average out near zero.
+        assert!(mean.len() < 0.05, "{:?}", mean);
+    }
+
+    #[test]
+    fn index_round_trips_every_axis() {
+        let mut v = Vec3::new(1., 2., 3.);
+        assert_eq!((v[0], v[1], v[2]), (1., 2., 3.));
+        for a in 0..3 {
+            v[a] = -(a as f32);
+        }
+        assert_eq!(v, Vec3::new(0., -1., -2.));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_past_z_panics() {
+        let v = Vec3::new(1., 2., 3.);
+        let _ = v[3];
+    }
+
+    #[test]
+    fn array_and_tuple_conversions_are_exact() {
+        let v = Vec3::new(0.1, -2.5e-7, 3.4e12);
+        let array: [f32; 3] = v.into();
+        assert_eq!(array, [0.1, -2.5e-7, 3.4e12]);
+        assert_eq!(Vec3::from(array), v);
+        assert_eq!(Vec3::from((0.1, -2.5e-7, 3.4e12)), v);
+    }
+
+    #[test]
+    fn lerp_hits_both_ends_and_the_midpoint() {
+        let (a, b) = (Vec3::new(1., 1., 1.), Vec3::new(0.5, 0.7, 1.));
+        assert_eq!(a.lerp(b, 0.), a);
+        assert_eq!(a.lerp(b, 1.), b);
+        assert!(a.lerp(b, 0.5).approx_eq((a + b) / 2., 1e-6));
+    }
+
+    #[test]
+    fn min_max_with_mixed_signs() {
+        let (a, b) = (Vec3::new(1., -2., 3.), Vec3::new(-1., 2., -3.));
+        assert_eq!(a.min(b), Vec3::new(-1., -2., -3.));
+        assert_eq!(a.max(b), Vec3::new(1., 2., 3.));
+        assert_eq!(a.min_component(), -2.);
+        assert_eq!(a.max_component(), 3.);
+        assert_eq!(b.min_component(), -3.);
+        assert_eq!(b.max_component(), 2.);
+    }
+
+    #[test]
+    fn scalar_first_mul_matches_vector_first() {
+        let v = Vec3::new(1., 2., 3.);
+        assert_eq!(2.0 * v, v * 2.0);
+        assert_eq!(-0.5 * v, Vec3::new(-0.5, -1., -1.5));
+    }
+
+    #[test]
+    fn clamp_limits_every_component() {
+        assert_eq!(
+            Vec3::new(-1., 0.5, 2.).clamp(0., 1.),
+            Vec3::new(0., 0.5, 1.)
+        );
+    }
+
+    #[test]
+    fn approx_eq_tolerates_tiny_differences_only() {
+        let a = Vec3::new(1., -2., 3.);
+        assert!(a.approx_eq(a + Vec3::new(1e-6, -1e-6, 1e-6), 1e-5));
+        assert!(!a.approx_eq(a + Vec3::new(0., 1e-2, 0.), 1e-5));
+    }
+
+    #[test]
+    fn cosine_sampling_converges_faster_than_uniform() {
+        crate::rng::reseed(62);
+        // Outgoing light from a white Lambertian floor under a sky of
+        // radiance z^2: the integral of z^2 cos / pi over the hemisphere, 0.5.
+        let up = Vec3::new(0., 0., 1.);
+        let cosine = || random_cosine_direction().z.powi(2);
+        let uniform = || {
+            let z = random_in_hemisphere(up).z;
+            2. * z.powi(3)
+        };
+        let mean_squared_error = |estimate: &dyn Fn() -> f32| {
+            let trials = 500;
+            let error: f32 = (0..trials)
+                .map(|_| {
+                    let mean = (0..32).map(|_| estimate()).sum::<f32>() / 32.;
+                    (mean - 0.5) * (mean - 0.5)
+                })
+                .sum();
+            error / trials as f32
+        };
+        let (cosine, uniform) = (mean_squared_error(&cosine), mean_squared_error(&uniform));
+        assert!(cosine < uniform / 2., "{} vs {}", cosine, uniform);
+    }
+
+    #[test]
+    fn reflect_flips_y_and_unit_ratio_refract_is_identity() {
+        let down = Vec3::new(0.6, -0.8, 0.);
+        let up = Vec3::new(0., 1., 0.);
+        assert_eq!(down.reflect(up), Vec3::new(0.6, 0.8, 0.));
+        assert!(down.refract(up, 1.).approx_eq(down, 1e-6));
+    }
+
+    #[test]
+    fn sqrt_and_abs_are_component_wise() {
+        assert_eq!(Vec3::new(4., 9., 16.).sqrt(), Vec3::new(2., 3., 4.));
+        assert_eq!(Vec3::new(-1., 2., -3.).abs(), Vec3::new(1., 2., 3.));
+        // Negatives clamp to zero rather than turning into NaN.
+        assert_eq!(Vec3::new(-4., 0., 1.).sqrt(), Vec3::new(0., 0., 1.));
+    }
+
+    #[test]
+    fn distance_of_a_3_4_5_triangle() {
+        let (a, b) = (Vec3::new(1., 2., 3.), Vec3::new(4., 6., 3.));
+        assert_eq!(a.distance(b), 5.);
+        assert_eq!(a.distance_squared(b), 25.);
+        assert_eq!(b.distance(a), 5.);
+    }
+
+    #[test]
+    fn perturb_deviation_grows_with_roughness() {
+        crate::rng::reseed(91);
+        let dir = Vec3::new(1., 2., -2.).unit();
+        for _ in 0..100 {
+            assert!(perturb(dir, 0.).approx_eq(dir, 1e-6));
+        }
+        // Mean angle between dir and its perturbed copies.
+        let deviation = |roughness| {
+            (0..2000)
+                .map(|_| clip(perturb(dir, roughness).dot(dir), -1., 1.).acos())
+                .sum::<f32>()
+                / 2000.
+        };
+        let (low, mid, high) = (deviation(0.1), deviation(0.5), deviation(1.));
+        assert!(
+            0. < low && low < mid && mid < high,
+            "{} {} {}",
+            low,
+            mid,
+            high
+        );
+    }
+
+    #[test]
+    fn project_and_reject_split_a_vector() {
+        let v = Vec3::new(1., 1., 0.);
+        let x = Vec3::new(3., 0., 0.);
+        assert_eq!(v.project_onto(x), Vec3::new(1., 0., 0.));
+        assert_eq!(v.reject_from(x), Vec3::new(0., 1., 0.));
+
+        let zero = Vec3::new(0., 0., 0.);
+        assert_eq!(v.project_onto(zero), zero);
+        assert_eq!(v.reject_from(zero), v);
+    }
+}
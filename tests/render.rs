@@ -0,0 +1,136 @@
+use rsay_tracing::{
+    add_sample_pass_with_seed, builtin_scene, load_scene, render, render_tiles_with_seed,
+    render_with_seed, Background, Camera, HittableList, Normalization, RenderConfig, Sampling,
+    Shading, Termination, Vec3,
+};
+
+fn config<'a>(camera: &'a Camera, world: &'a HittableList) -> RenderConfig<'a> {
+    RenderConfig {
+        image_width: 2,
+        image_height: 2,
+        samples_per_pixel: 4,
+        max_depth: 8,
+        camera,
+        world,
+        lights: None,
+        background: Background::sky(),
+        sampling: Sampling::Random,
+        shading: Shading::Materials,
+        termination: Termination::FixedDepth,
+        min_bounces: 0,
+        firefly_clamp: None,
+        normalization: Normalization::Exact,
+        threads: None,
+        quiet: true,
+    }
+}
+
+#[test]
+fn renders_a_2x2_image() {
+    let (camera, world) = builtin_scene(1.);
+    let image = render(config(&camera, &world)).unwrap();
+    assert_eq!(image.len(), 4);
+    assert!(image
+        .iter()
+        .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite()));
+}
+
+#[test]
+fn tiles_match_scanlines() {
+    let (camera, world) = builtin_scene(16. / 9.);
+    let config = || RenderConfig {
+        image_width: 16,
+        image_height: 9,
+        ..config(&camera, &world)
+    };
+    let scanlines = render_with_seed(7, config()).unwrap();
+    for tile_size in [1, 4, 5, 32] {
+        assert_eq!(
+            render_tiles_with_seed(7, config(), tile_size).unwrap(),
+            scanlines
+        );
+    }
+}
+
+#[test]
+fn seeded_passes_match_a_seeded_render() {
+    let (camera, world) = builtin_scene(16. / 9.);
+    for sampling in [Sampling::Random, Sampling::Stratified] {
+        let config = || RenderConfig {
+            image_width: 16,
+            image_height: 9,
+            samples_per_pixel: 10,
+            sampling,
+            ..config(&camera, &world)
+        };
+        let full = render_with_seed(11, config()).unwrap();
+
+        let mut accum = vec![Vec3::new(0., 0., 0.); full.len()];
+        let mut count = 0;
+        for _ in 0..10 {
+            add_sample_pass_with_seed(11, &mut accum, &mut count, &config()).unwrap();
+        }
+        assert_eq!(count, 10);
+        assert_eq!(accum, full);
+    }
+}
+
+#[test]
+fn seeded_render_is_identical_across_thread_counts() {
+    let (camera, world) = builtin_scene(16. / 9.);
+    let config = |threads| RenderConfig {
+        image_width: 16,
+        image_height: 9,
+        threads,
+        ..config(&camera, &world)
+    };
+    let single = render_with_seed(3, config(Some(1))).unwrap();
+    assert_eq!(render_with_seed(3, config(Some(8))).unwrap(), single);
+}
+
+#[test]
+fn different_seeds_give_different_renders() {
+    let (camera, world) = builtin_scene(16. / 9.);
+    let config = || RenderConfig {
+        image_width: 16,
+        image_height: 9,
+        ..config(&camera, &world)
+    };
+    let first = render_with_seed(3, config()).unwrap();
+    assert_ne!(render_with_seed(4, config()).unwrap(), first);
+}
+
+#[test]
+fn loaded_json_scene_renders() {
+    let json = r#"{
+        "camera": { "lookfrom": [0, 0, 1], "lookat": [0, 0, -1], "vfov": 60 },
+        "materials": { "glass": { "type": "dielectric", "ir": 1.5 } },
+        "objects": [
+            { "type": "sphere", "center": [0, 0, -1], "radius": 0.5, "material": "glass" },
+            { "type": "sphere", "center": [0, -100.5, -1], "radius": 100,
+              "material": { "type": "lambertian", "albedo": [0.8, 0.8, 0] } },
+            { "type": "sphere", "center": [0, 2, -1], "radius": 0.5,
+              "material": { "type": "diffuse_light", "emit": [4, 4, 4] } }
+        ]
+    }"#;
+    let file = format!("rsay-tracing-{}-scene.json", std::process::id());
+    let path = std::env::temp_dir().join(file);
+    std::fs::write(&path, json).unwrap();
+    let scene = load_scene(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    let scene = scene.unwrap();
+    let camera = scene.camera.build(4. / 3.);
+    let (world, lights) = scene.world_and_lights().unwrap();
+    let image = render(RenderConfig {
+        image_width: 8,
+        image_height: 6,
+        lights: Some(&lights),
+        ..config(&camera, &world)
+    })
+    .unwrap();
+    assert_eq!(image.len(), 8 * 6);
+    assert!(image
+        .iter()
+        .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite()));
+}